@@ -0,0 +1,238 @@
+//! Support for reading the binary `COPY` format.
+//!
+//! The binary format is produced by `COPY ... TO STDOUT WITH (FORMAT binary)`
+//! statements and by `COPY ... TO 'file' WITH (FORMAT binary)` on the server.
+//! `BinaryCopyReader` parses it from any `Read`er into rows whose fields can
+//! be retrieved with the normal `FromSql` machinery.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! # use postgres::{Connection, SslMode};
+//! use postgres::binary_copy::BinaryCopyReader;
+//!
+//! # let conn = Connection::connect("", &SslMode::None).unwrap();
+//! let types = conn.prepare("SELECT id, name FROM foo").unwrap()
+//!                 .columns().iter().map(|c| c.type_().clone()).collect();
+//!
+//! let stmt = conn.prepare("COPY foo (id, name) TO STDOUT WITH (FORMAT binary)").unwrap();
+//! let mut reader = BinaryCopyReader::new(stmt.copy_out_reader(&[]).unwrap(), types);
+//! for row in &mut reader {
+//!     let row = row.unwrap();
+//!     let id: i32 = row.get(0);
+//!     let name: Option<String> = row.get(1);
+//!     println!("{}: {:?}", id, name);
+//! }
+//! reader.into_inner().finish().unwrap();
+//! ```
+use byteorder::{ReadBytesExt, BigEndian};
+use debug_builders::DebugStruct;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::rc::Rc;
+
+use {Result, Error};
+use types::{Type, FromSql};
+use util;
+
+const SIGNATURE: &'static [u8] = b"PGCOPY\n\xff\r\n\x00";
+
+// Bit 16 of the flags field indicates that each row is preceded by its OID
+const HAS_OIDS: i32 = 1 << 16;
+
+fn invalid<T>(msg: &'static str) -> Result<T> {
+    Err(Error::IoError(io::Error::new(io::ErrorKind::InvalidInput, msg)))
+}
+
+/// A reader which parses a binary `COPY` stream into rows.
+pub struct BinaryCopyReader<R> {
+    reader: R,
+    types: Rc<Vec<Type>>,
+    has_oids: Option<bool>,
+    done: bool,
+}
+
+impl<R> fmt::Debug for BinaryCopyReader<R> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        DebugStruct::new(fmt, "BinaryCopyReader")
+            .field("types", &self.types)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<R: Read> BinaryCopyReader<R> {
+    /// Creates a new `BinaryCopyReader`.
+    ///
+    /// `types` specifies the Postgres type of each column of the data.
+    pub fn new(reader: R, types: Vec<Type>) -> BinaryCopyReader<R> {
+        BinaryCopyReader {
+            reader: reader,
+            types: Rc::new(types),
+            has_oids: None,
+            done: false,
+        }
+    }
+
+    /// Returns a slice containing the expected column types.
+    pub fn types(&self) -> &[Type] {
+        &self.types
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_header(&mut self) -> Result<bool> {
+        let mut signature = [0; 11];
+        try!(util::read_all(&mut self.reader, &mut signature));
+        if &signature[..] != SIGNATURE {
+            return invalid("invalid binary COPY signature");
+        }
+
+        let flags = try!(self.reader.read_i32::<BigEndian>());
+        let extension_len = try!(self.reader.read_i32::<BigEndian>());
+        if extension_len < 0 {
+            return invalid("invalid binary COPY header extension length");
+        }
+        let mut extension = vec![0; extension_len as usize];
+        try!(util::read_all(&mut self.reader, &mut extension));
+
+        Ok(flags & HAS_OIDS != 0)
+    }
+
+    fn read_field(&mut self) -> Result<Option<Vec<u8>>> {
+        match try!(self.reader.read_i32::<BigEndian>()) {
+            -1 => Ok(None),
+            len if len < 0 => invalid("invalid binary COPY field length"),
+            len => {
+                let mut data = vec![0; len as usize];
+                try!(util::read_all(&mut self.reader, &mut data));
+                Ok(Some(data))
+            }
+        }
+    }
+
+    /// Reads the next row of data, returning `None` once the trailer has been
+    /// reached.
+    pub fn next_row(&mut self) -> Result<Option<BinaryCopyRow>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let has_oids = match self.has_oids {
+            Some(has_oids) => has_oids,
+            None => {
+                let has_oids = try!(self.read_header());
+                self.has_oids = Some(has_oids);
+                has_oids
+            }
+        };
+
+        let count = match try!(self.reader.read_i16::<BigEndian>()) {
+            -1 => {
+                self.done = true;
+                return Ok(None);
+            }
+            count => count,
+        };
+        if count < 0 || count as usize != self.types.len() {
+            return invalid("unexpected column count in binary COPY data");
+        }
+
+        if has_oids {
+            try!(self.read_field());
+        }
+
+        let mut data = Vec::with_capacity(self.types.len());
+        for _ in 0..count {
+            data.push(try!(self.read_field()));
+        }
+
+        Ok(Some(BinaryCopyRow {
+            types: self.types.clone(),
+            data: data,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for BinaryCopyReader<R> {
+    type Item = Result<BinaryCopyRow>;
+
+    fn next(&mut self) -> Option<Result<BinaryCopyRow>> {
+        match self.next_row() {
+            Ok(Some(row)) => Some(Ok(row)),
+            Ok(None) => None,
+            Err(err) => {
+                // the stream is in an unknown state, so don't try to continue
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// A row of data parsed from a binary `COPY` stream.
+pub struct BinaryCopyRow {
+    types: Rc<Vec<Type>>,
+    data: Vec<Option<Vec<u8>>>,
+}
+
+impl fmt::Debug for BinaryCopyRow {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        DebugStruct::new(fmt, "BinaryCopyRow")
+            .field("types", &self.types)
+            .finish()
+    }
+}
+
+impl BinaryCopyRow {
+    /// Returns the number of values in the row.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns a slice containing the types of the values in the row.
+    pub fn types(&self) -> &[Type] {
+        &self.types
+    }
+
+    /// Retrieves the contents of a field of the row.
+    ///
+    /// Returns an `Error` value if the index does not reference a column or
+    /// the return type is not compatible with the Postgres type.
+    pub fn get_opt<T>(&self, idx: usize) -> Result<T> where T: FromSql {
+        let ty = match self.types.get(idx) {
+            Some(ty) => ty,
+            None => return Err(Error::InvalidColumn),
+        };
+        if !<T as FromSql>::accepts(ty) {
+            return Err(Error::WrongType(ty.clone()));
+        }
+        FromSql::from_sql_nullable(ty, self.data[idx].as_ref().map(|e| &**e).as_mut())
+    }
+
+    /// Retrieves the contents of a field of the row.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the index does not reference a column or the return type is
+    /// not compatible with the Postgres type.
+    pub fn get<T>(&self, idx: usize) -> T where T: FromSql {
+        match self.get_opt(idx) {
+            Ok(ok) => ok,
+            Err(err) => panic!("error retrieving column {}: {:?}", idx, err),
+        }
+    }
+
+    /// Retrieves the specified field as a raw buffer of Postgres data.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the index does not reference a column.
+    pub fn get_bytes(&self, idx: usize) -> Option<&[u8]> {
+        self.data[idx].as_ref().map(|e| &**e)
+    }
+}
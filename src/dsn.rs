@@ -0,0 +1,103 @@
+// Parsing of libpq-style `key=value` connection strings.
+//
+// Pairs are separated by whitespace, and whitespace is allowed around the
+// `=`. Values containing whitespace must be wrapped in single quotes. A
+// backslash escapes the following character, in quoted and unquoted values
+// alike.
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub type ParseResult<T> = Result<T, String>;
+
+struct Parser<'a> {
+    it: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.it.peek().map_or(false, |c| c.is_whitespace()) {
+            self.it.next();
+        }
+    }
+
+    fn key(&mut self) -> ParseResult<String> {
+        let mut key = String::new();
+        loop {
+            let c = match self.it.peek() {
+                Some(&c) if c != '=' && !c.is_whitespace() => c,
+                _ => break,
+            };
+            self.it.next();
+            key.push(c);
+        }
+
+        if key.is_empty() {
+            return Err("expected a parameter name".to_owned());
+        }
+        Ok(key)
+    }
+
+    fn equals(&mut self, key: &str) -> ParseResult<()> {
+        self.skip_whitespace();
+        match self.it.next() {
+            Some('=') => {}
+            _ => return Err(format!("missing \"=\" after \"{}\"", key)),
+        }
+        self.skip_whitespace();
+        Ok(())
+    }
+
+    fn escaped(&mut self, key: &str) -> ParseResult<char> {
+        match self.it.next() {
+            Some(c) => Ok(c),
+            None => Err(format!("trailing backslash in value of \"{}\"", key)),
+        }
+    }
+
+    fn value(&mut self, key: &str) -> ParseResult<String> {
+        let mut value = String::new();
+
+        if self.it.peek() == Some(&'\'') {
+            self.it.next();
+            loop {
+                match self.it.next() {
+                    Some('\'') => return Ok(value),
+                    Some('\\') => value.push(try!(self.escaped(key))),
+                    Some(c) => value.push(c),
+                    None => return Err(format!("unterminated quoted value for \"{}\"", key)),
+                }
+            }
+        }
+
+        loop {
+            let c = match self.it.peek() {
+                Some(&c) if !c.is_whitespace() => c,
+                _ => break,
+            };
+            self.it.next();
+            if c == '\\' {
+                value.push(try!(self.escaped(key)));
+            } else {
+                value.push(c);
+            }
+        }
+        Ok(value)
+    }
+}
+
+pub fn parse(s: &str) -> ParseResult<Vec<(String, String)>> {
+    let mut parser = Parser { it: s.chars().peekable() };
+    let mut pairs = vec![];
+
+    loop {
+        parser.skip_whitespace();
+        if parser.it.peek().is_none() {
+            return Ok(pairs);
+        }
+
+        let key = try!(parser.key());
+        try!(parser.equals(&key));
+        let value = try!(parser.value(&key));
+        pairs.push((key, value));
+    }
+}
@@ -1,14 +1,13 @@
 pub use ugh_privacy::DbError;
 
 use byteorder;
-use openssl::ssl::error::SslError;
 use phf;
 use std::error;
 use std::convert::From;
 use std::fmt;
 use std::io;
 
-use Result;
+use {Result, TargetSessionAttrs};
 use types::Type;
 
 include!(concat!(env!("OUT_DIR"), "/sqlstate.rs"));
@@ -16,7 +15,7 @@ include!(concat!(env!("OUT_DIR"), "/sqlstate.rs"));
 /// Reasons a new Postgres connection could fail.
 #[derive(Debug)]
 pub enum ConnectError {
-    /// The provided URL could not be parsed.
+    /// The provided URL or connection string could not be parsed.
     InvalidUrl(String),
     /// The URL was missing a user.
     MissingUser,
@@ -30,11 +29,16 @@ pub enum ConnectError {
     /// The Postgres server does not support SSL encryption.
     NoSslSupport,
     /// There was an error initializing the SSL session.
-    SslError(SslError),
+    SslError(Box<error::Error + Sync + Send>),
     /// There was an error communicating with the server.
     IoError(io::Error),
     /// The server sent an unexpected response.
     BadResponse,
+    /// A connect, read or write timeout expired.
+    Timeout,
+    /// The session did not have the properties requested by
+    /// `ConnectParams::target_session_attrs`.
+    WrongSessionAttrs(TargetSessionAttrs),
 }
 
 impl fmt::Display for ConnectError {
@@ -42,6 +46,8 @@ impl fmt::Display for ConnectError {
         try!(fmt.write_str(error::Error::description(self)));
         match *self {
             ConnectError::InvalidUrl(ref msg) => write!(fmt, ": {}", msg),
+            ConnectError::SslError(ref err) => write!(fmt, ": {}", err),
+            ConnectError::WrongSessionAttrs(ref attrs) => write!(fmt, ": expected {:?}", attrs),
             _ => Ok(())
         }
     }
@@ -61,22 +67,38 @@ impl error::Error for ConnectError {
             ConnectError::SslError(_) => "Error initiating SSL session",
             ConnectError::IoError(_) => "Error communicating with server",
             ConnectError::BadResponse => "The server returned an unexpected response",
+            ConnectError::Timeout => "Timed out communicating with the server",
+            ConnectError::WrongSessionAttrs(_) => {
+                "The session did not have the requested attributes"
+            }
         }
     }
 
     fn cause(&self) -> Option<&error::Error> {
         match *self {
             ConnectError::DbError(ref err) => Some(err),
-            ConnectError::SslError(ref err) => Some(err),
+            ConnectError::SslError(ref err) => Some(&**err),
             ConnectError::IoError(ref err) => Some(err),
             _ => None
         }
     }
 }
 
+fn is_timeout(err: &io::Error) -> bool {
+    // Read and write timeouts are reported as `WouldBlock` on some platforms
+    match err.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => true,
+        _ => false,
+    }
+}
+
 impl From<io::Error> for ConnectError {
     fn from(err: io::Error) -> ConnectError {
-        ConnectError::IoError(err)
+        if is_timeout(&err) {
+            ConnectError::Timeout
+        } else {
+            ConnectError::IoError(err)
+        }
     }
 }
 
@@ -86,15 +108,9 @@ impl From<DbError> for ConnectError {
     }
 }
 
-impl From<SslError> for ConnectError {
-    fn from(err: SslError) -> ConnectError {
-        ConnectError::SslError(err)
-    }
-}
-
 impl From<byteorder::Error> for ConnectError {
     fn from(err: byteorder::Error) -> ConnectError {
-        ConnectError::IoError(From::from(err))
+        From::from(io::Error::from(err))
     }
 }
 
@@ -131,6 +147,28 @@ pub enum Error {
     WasNull,
     /// The server returned an unexpected response.
     BadResponse,
+    /// A value passed to `CopyInStatement::execute` could not be converted
+    /// to the Postgres type of its column.
+    CopyInConversion {
+        /// The 0-based index of the row containing the value.
+        row: usize,
+        /// The 0-based index of the column containing the value.
+        column: usize,
+        /// The error returned by the conversion.
+        error: Box<Error>,
+    },
+    /// A row passed to `CopyInStatement::execute` did not contain the
+    /// expected number of values.
+    CopyInColumnCount {
+        /// The 0-based index of the row.
+        row: usize,
+        /// The number of values expected in each row.
+        expected: usize,
+    },
+    /// A `COPY FROM STDIN` operation was aborted by the caller.
+    CopyInAborted(Box<error::Error+Sync+Send>),
+    /// A read or write timeout expired while communicating with the server.
+    Timeout,
 }
 
 impl fmt::Display for Error {
@@ -138,6 +176,13 @@ impl fmt::Display for Error {
         try!(fmt.write_str(error::Error::description(self)));
         match *self {
             Error::WrongType(ref ty) => write!(fmt, ": saw type {:?}", ty),
+            Error::CopyInConversion { row, column, ref error } => {
+                write!(fmt, " (row {}, column {}): {}", row, column, error)
+            }
+            Error::CopyInColumnCount { row, expected } => {
+                write!(fmt, " (row {}): expected {} values", row, expected)
+            }
+            Error::CopyInAborted(ref err) => write!(fmt, ": {}", err),
             _ => Ok(()),
         }
     }
@@ -155,6 +200,10 @@ impl error::Error for Error {
             Error::InvalidColumn => "Invalid column",
             Error::WasNull => "The value was NULL",
             Error::BadResponse => "The server returned an unexpected response",
+            Error::CopyInConversion { .. } => "Unable to convert a value for COPY",
+            Error::CopyInColumnCount { .. } => "Invalid column count for COPY",
+            Error::CopyInAborted(_) => "The COPY was aborted",
+            Error::Timeout => "Timed out communicating with the Postgres server",
         }
     }
 
@@ -162,6 +211,8 @@ impl error::Error for Error {
         match *self {
             Error::DbError(ref err) => Some(err),
             Error::IoError(ref err) => Some(err),
+            Error::CopyInConversion { ref error, .. } => Some(&**error),
+            Error::CopyInAborted(ref err) => Some(&**err),
             _ => None
         }
     }
@@ -175,12 +226,16 @@ impl From<DbError> for Error {
 
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Error {
-        Error::IoError(err)
+        if is_timeout(&err) {
+            Error::Timeout
+        } else {
+            Error::IoError(err)
+        }
     }
 }
 
 impl From<byteorder::Error> for Error {
     fn from(err: byteorder::Error) -> Error {
-        Error::IoError(From::from(err))
+        From::from(io::Error::from(err))
     }
 }
@@ -1,17 +1,32 @@
-use openssl::ssl::{SslStream, MaybeSslStream};
+#[cfg(feature = "with-openssl")]
+use openssl::ssl::{SslContext, SslMethod};
+#[cfg(feature = "with-openssl")]
+use openssl::ssl::error::SslError;
+#[cfg(feature = "with-openssl")]
+use openssl::x509::X509FileType;
+use std::fmt;
 use std::io;
 use std::io::prelude::*;
-use std::net::TcpStream;
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::mem;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+#[cfg(unix)]
+use libc;
 #[cfg(feature = "unix_socket")]
 use unix_socket::UnixStream;
 use byteorder::ReadBytesExt;
+use net2::TcpStreamExt;
 
-use {ConnectParams, SslMode, ConnectTarget, ConnectError};
+use {ConnectParams, SslMode, SslModeKind, SslOptions, ConnectTarget, ConnectError, TcpOptions};
 use message;
 use message::WriteMessage;
 use message::FrontendMessage::SslRequest;
+use ssl::{NegotiateSsl, StreamWrapper, Verification};
 
-const DEFAULT_PORT: u16 = 5432;
+pub const DEFAULT_PORT: u16 = 5432;
 
 pub enum InternalStream {
     Tcp(TcpStream),
@@ -47,29 +62,358 @@ impl Write for InternalStream {
     }
 }
 
+impl fmt::Debug for InternalStream {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InternalStream::Tcp(ref s) => fmt::Debug::fmt(s, fmt),
+            #[cfg(feature = "unix_socket")]
+            InternalStream::Unix(ref s) => fmt::Debug::fmt(s, fmt),
+        }
+    }
+}
+
+impl InternalStream {
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match *self {
+            InternalStream::Tcp(ref s) => s.set_read_timeout(timeout),
+            #[cfg(feature = "unix_socket")]
+            InternalStream::Unix(ref s) => s.set_read_timeout(timeout),
+        }
+    }
+
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match *self {
+            InternalStream::Tcp(ref s) => s.set_write_timeout(timeout),
+            #[cfg(feature = "unix_socket")]
+            InternalStream::Unix(ref s) => s.set_write_timeout(timeout),
+        }
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match *self {
+            InternalStream::Tcp(ref s) => s.set_nonblocking(nonblocking),
+            #[cfg(feature = "unix_socket")]
+            InternalStream::Unix(ref s) => s.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            InternalStream::Tcp(ref s) => s.peek(buf),
+            #[cfg(feature = "unix_socket")]
+            InternalStream::Unix(ref s) => {
+                let ret = unsafe {
+                    libc::recv(s.as_raw_fd(),
+                               buf.as_mut_ptr() as *mut libc::c_void,
+                               buf.len(),
+                               libc::MSG_PEEK)
+                };
+                if ret < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(ret as usize)
+                }
+            }
+        }
+    }
+
+    // Waits for data to be readable without consuming any of it, so an SSL
+    // session layered on top of the socket is never interrupted mid-record.
+    // If `timeout` is `None`, only data which has already arrived is
+    // considered. The socket's read timeout is reset to `read_timeout`.
+    pub fn wait_readable(&self, timeout: Option<Duration>, read_timeout: Option<Duration>)
+                         -> io::Result<bool> {
+        match timeout {
+            Some(timeout) => try!(self.set_read_timeout(Some(timeout))),
+            None => try!(self.set_nonblocking(true)),
+        }
+
+        // EOF counts as readable, since the next read will report it
+        let result = match self.peek(&mut [0]) {
+            Ok(_) => Ok(true),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock
+                            || err.kind() == io::ErrorKind::TimedOut => Ok(false),
+            Err(err) => Err(err),
+        };
+
+        try!(self.set_nonblocking(false));
+        try!(self.set_read_timeout(read_timeout));
+        result
+    }
+}
+
+// Sits between a connection's `BufStream` and its `StreamWrapper`. While
+// closed, reads fail with `WouldBlock` without reaching the wrapper, which
+// lets the connection check the `BufStream` for buffered data without
+// touching an SSL session.
+pub struct GatedStream {
+    stream: Box<StreamWrapper>,
+    open: bool,
+}
+
+impl GatedStream {
+    pub fn new(stream: Box<StreamWrapper>) -> GatedStream {
+        GatedStream {
+            stream: stream,
+            open: true,
+        }
+    }
+
+    pub fn get_ref(&self) -> &StreamWrapper {
+        &*self.stream
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+}
+
+impl Read for GatedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.open {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "the stream is gated"));
+        }
+        self.stream.read(buf)
+    }
+}
+
+impl Write for GatedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+/// A connection to the Postgres server.
+///
+/// It implements `Read` and `Write`, and is passed to `NegotiateSsl`
+/// implementations to be wrapped in an SSL session.
+#[derive(Debug)]
+pub struct Stream(InternalStream);
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+// Not a method, so that it stays out of `Stream`'s public interface.
+pub fn internal(stream: &Stream) -> &InternalStream {
+    &stream.0
+}
+
+// Like libpq, the timeout applies to each of the host's addresses in turn
+fn connect_tcp(host: &str, port: u16, timeout: Option<Duration>) -> io::Result<TcpStream> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return TcpStream::connect(&(host, port)),
+    };
+
+    let mut last_err = None;
+    for addr in try!((host, port).to_socket_addrs()) {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "could not resolve any addresses")
+    }))
+}
+
+#[cfg(unix)]
+fn setsockopt<T: AsRawFd>(socket: &T,
+                          level: libc::c_int,
+                          name: libc::c_int,
+                          value: libc::c_int)
+                          -> io::Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(socket.as_raw_fd(),
+                         level,
+                         name,
+                         &value as *const libc::c_int as *const libc::c_void,
+                         mem::size_of::<libc::c_int>() as libc::socklen_t)
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+// net2 can only enable keepalives along with an idle time, so the option is
+// set directly to keep the operating system's default
+#[cfg(unix)]
+fn enable_keepalive(stream: &TcpStream) -> io::Result<()> {
+    setsockopt(stream, libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1)
+}
+
+// Windows requires an idle time, so use its default of two hours
+#[cfg(windows)]
+fn enable_keepalive(stream: &TcpStream) -> io::Result<()> {
+    stream.set_keepalive(Some(Duration::from_secs(2 * 60 * 60)))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_keepalive_probes(stream: &TcpStream, options: &TcpOptions) -> io::Result<()> {
+    if let Some(interval) = options.keepalives_interval {
+        try!(setsockopt(stream,
+                        libc::IPPROTO_TCP,
+                        libc::TCP_KEEPINTVL,
+                        interval.as_secs() as libc::c_int));
+    }
+    if let Some(count) = options.keepalives_count {
+        try!(setsockopt(stream, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, count as libc::c_int));
+    }
+    Ok(())
+}
+
+// Other platforms don't allow the probes to be configured
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_keepalive_probes(_: &TcpStream, _: &TcpOptions) -> io::Result<()> {
+    Ok(())
+}
+
+fn configure_tcp(stream: &TcpStream, options: &TcpOptions) -> io::Result<()> {
+    try!(stream.set_nodelay(options.tcp_nodelay));
+
+    if options.keepalives {
+        match options.keepalives_idle {
+            Some(idle) => try!(stream.set_keepalive(Some(idle))),
+            None => try!(enable_keepalive(stream)),
+        }
+        try!(set_keepalive_probes(stream, options));
+    } else {
+        try!(stream.set_keepalive(None));
+    }
+
+    if let Some(size) = options.send_buffer_size {
+        try!(stream.set_send_buffer_size(size));
+    }
+    if let Some(size) = options.recv_buffer_size {
+        try!(stream.set_recv_buffer_size(size));
+    }
+    Ok(())
+}
+
 fn open_socket(params: &ConnectParams) -> Result<InternalStream, ConnectError> {
     let port = params.port.unwrap_or(DEFAULT_PORT);
-    match params.target {
+    let socket = match params.target {
         ConnectTarget::Tcp(ref host) => {
-            Ok(try!(TcpStream::connect(&(&**host, port)).map(InternalStream::Tcp)))
+            let stream = try!(connect_tcp(host, port, params.connect_timeout));
+            try!(configure_tcp(&stream, &params.tcp));
+            InternalStream::Tcp(stream)
         }
         #[cfg(feature = "unix_socket")]
         ConnectTarget::Unix(ref path) => {
             let mut path = path.clone();
             path.push(&format!(".s.PGSQL.{}", port));
-            Ok(try!(UnixStream::connect(&path).map(InternalStream::Unix)))
+            InternalStream::Unix(try!(UnixStream::connect(&path)))
         }
+    };
+
+    try!(socket.set_read_timeout(params.read_timeout));
+    try!(socket.set_write_timeout(params.write_timeout));
+    Ok(socket)
+}
+
+#[cfg(feature = "with-openssl")]
+fn ssl_context(options: &SslOptions) -> Result<SslContext, SslError> {
+    let mut ctx = try!(SslContext::new(SslMethod::Sslv23));
+    match options.root_cert {
+        Some(ref path) => try!(ctx.set_CA_file(path)),
+        None => try!(ctx.set_default_verify_paths()),
+    }
+    if let Some(ref path) = options.cert {
+        try!(ctx.set_certificate_file(path, X509FileType::PEM));
+    }
+    if let Some(ref path) = options.key {
+        try!(ctx.set_private_key_file(path, X509FileType::PEM));
+    }
+    Ok(ctx)
+}
+
+#[cfg(feature = "with-openssl")]
+fn negotiator(options: &SslOptions) -> Result<Box<NegotiateSsl>, ConnectError> {
+    match ssl_context(options) {
+        Ok(ctx) => Ok(Box::new(ctx)),
+        Err(err) => Err(ConnectError::SslError(Box::new(err))),
+    }
+}
+
+#[cfg(not(feature = "with-openssl"))]
+fn negotiator(_: &SslOptions) -> Result<Box<NegotiateSsl>, ConnectError> {
+    Err(ConnectError::SslError("the sslmode parameter requires the `with-openssl` feature".into()))
+}
+
+// Builds the `SslMode` requested by the connection parameters, if any.
+fn ssl_mode_from_params(options: &SslOptions) -> Result<Option<SslMode>, ConnectError> {
+    let kind = match options.mode {
+        Some(SslModeKind::Disable) => return Ok(Some(SslMode::None)),
+        Some(kind) => kind,
+        None => return Ok(None),
+    };
+
+    let negotiator = try!(negotiator(options));
+    let mode = match kind {
+        SslModeKind::Disable => unreachable!(),
+        SslModeKind::Prefer => SslMode::Prefer(negotiator),
+        SslModeKind::Require => SslMode::Require(negotiator),
+        SslModeKind::VerifyCa => SslMode::VerifyCa(negotiator),
+        SslModeKind::VerifyFull => SslMode::VerifyFull(negotiator),
+    };
+    Ok(Some(mode))
+}
+
+fn ssl_mode_kind(ssl: &SslMode) -> SslModeKind {
+    match *ssl {
+        SslMode::None => SslModeKind::Disable,
+        SslMode::Prefer(_) => SslModeKind::Prefer,
+        SslMode::Require(_) => SslModeKind::Require,
+        SslMode::VerifyCa(_) => SslModeKind::VerifyCa,
+        SslMode::VerifyFull(_) => SslModeKind::VerifyFull,
     }
 }
 
 pub fn initialize_stream(params: &ConnectParams, ssl: &SslMode)
-                         -> Result<MaybeSslStream<InternalStream>, ConnectError> {
-    let mut socket = try!(open_socket(params));
+                         -> Result<Box<StreamWrapper>, ConnectError> {
+    // An explicit `SslMode` takes precedence over the parameters, but the
+    // two may not ask for different modes
+    let param_ssl = match *ssl {
+        SslMode::None => try!(ssl_mode_from_params(&params.ssl)),
+        _ => match params.ssl.mode {
+            Some(kind) if kind != ssl_mode_kind(ssl) => {
+                return Err(ConnectError::SslError("the sslmode parameter conflicts with the \
+                                                   SslMode passed to connect"
+                                                      .into()));
+            }
+            _ => None,
+        },
+    };
+    let ssl = param_ssl.as_ref().unwrap_or(ssl);
+
+    let mut socket = Stream(try!(open_socket(params)));
 
-    let (ssl_required, ctx) = match *ssl {
-        SslMode::None => return Ok(MaybeSslStream::Normal(socket)),
-        SslMode::Prefer(ref ctx) => (false, ctx),
-        SslMode::Require(ref ctx) => (true, ctx)
+    let (ssl_required, negotiator, verification) = match *ssl {
+        SslMode::None => return Ok(Box::new(socket)),
+        SslMode::Prefer(ref negotiator) => (false, negotiator, Verification::Default),
+        SslMode::Require(ref negotiator) => (true, negotiator, Verification::Default),
+        SslMode::VerifyCa(ref negotiator) => (true, negotiator, Verification::Ca),
+        SslMode::VerifyFull(ref negotiator) => (true, negotiator, Verification::Full),
     };
 
     try!(socket.write_message(&SslRequest { code: message::SSL_CODE }));
@@ -79,12 +423,18 @@ pub fn initialize_stream(params: &ConnectParams, ssl: &SslMode)
         if ssl_required {
             return Err(ConnectError::NoSslSupport);
         } else {
-            return Ok(MaybeSslStream::Normal(socket));
+            return Ok(Box::new(socket));
         }
     }
 
-    match SslStream::new(ctx, socket) {
-        Ok(stream) => Ok(MaybeSslStream::Ssl(stream)),
-        Err(err) => Err(ConnectError::SslError(err))
+    let host = match params.target {
+        ConnectTarget::Tcp(ref host) => &**host,
+        #[cfg(feature = "unix_socket")]
+        ConnectTarget::Unix(_) => "localhost",
+    };
+
+    match negotiator.negotiate_ssl(host, verification, socket) {
+        Ok(stream) => Ok(stream),
+        Err(err) => Err(ConnectError::SslError(err)),
     }
 }
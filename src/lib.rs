@@ -47,34 +47,46 @@
 
 extern crate bufstream;
 extern crate byteorder;
+extern crate hmac;
+#[cfg(unix)]
+extern crate libc;
 #[macro_use]
 extern crate log;
+extern crate md5;
+#[cfg(feature = "with-openssl")]
 extern crate openssl;
+#[cfg(feature = "with-openssl")]
+extern crate openssl_verify;
 extern crate phf;
+extern crate rand;
 extern crate rustc_serialize as serialize;
+extern crate sha2;
 #[cfg(feature = "unix_socket")]
 extern crate unix_socket;
 extern crate debug_builders;
+extern crate net2;
 
 use bufstream::BufStream;
 use debug_builders::DebugStruct;
-use openssl::crypto::hash::{self, Hasher};
-use openssl::ssl::{SslContext, MaybeSslStream};
-use serialize::hex::ToHex;
+use rand::Rng;
 use std::ascii::AsciiExt;
 use std::borrow::{ToOwned, Cow};
 use std::cell::{Cell, RefCell};
+use std::cmp;
 use std::collections::{VecDeque, HashMap};
+use std::env;
+use std::error::Error as StdError;
 use std::fmt;
 use std::iter::IntoIterator;
 use std::io;
 use std::io::prelude::*;
 use std::mem;
 use std::slice;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 use std::result;
 use std::vec;
 use byteorder::{WriteBytesExt, BigEndian};
-#[cfg(feature = "unix_socket")]
 use std::path::PathBuf;
 
 pub use error::{Error, ConnectError, SqlState, DbError, ErrorPosition};
@@ -83,25 +95,35 @@ pub use types::{Oid, Type, Kind, ToSql, FromSql};
 use types::IsNull;
 #[doc(inline)]
 pub use types::Slice;
-use io_util::InternalStream;
+use io_util::GatedStream;
 use message::BackendMessage::*;
 use message::FrontendMessage::*;
 use message::{FrontendMessage, BackendMessage, RowDescriptionEntry};
 use message::{WriteMessage, ReadMessage};
+use scram::ScramSha256;
+use ssl::NegotiateSsl;
 use url::Url;
 
 #[macro_use]
 mod macros;
 
+pub mod binary_copy;
+mod dsn;
 pub mod error;
 mod io_util;
 mod message;
+mod pgpass;
+pub mod pool;
+mod scram;
+pub mod ssl;
 mod ugh_privacy;
 mod url;
 mod util;
 pub mod types;
 
 const TYPEINFO_QUERY: &'static str = "t";
+const TYPEINFO_ENUM_QUERY: &'static str = "e";
+const COPY_IN_CHUNK_SIZE: usize = 16 * 1024;
 
 /// A type alias of the result returned by many methods.
 pub type Result<T> = result::Result<T, Error>;
@@ -144,6 +166,166 @@ pub struct ConnectParams {
     pub database: Option<String>,
     /// Runtime parameters to be passed to the Postgres backend.
     pub options: Vec<(String, String)>,
+    /// The amount of time to wait when opening a TCP connection to each of
+    /// the server's addresses.
+    ///
+    /// Set by the `connect_timeout` URL query parameter, in seconds. Defaults
+    /// to waiting indefinitely.
+    pub connect_timeout: Option<Duration>,
+    /// The amount of time to wait for data from the server before failing
+    /// with a timeout error.
+    ///
+    /// Set by the `read_timeout` URL query parameter, in seconds. Defaults to
+    /// waiting indefinitely.
+    pub read_timeout: Option<Duration>,
+    /// The amount of time to wait when sending data to the server before
+    /// failing with a timeout error.
+    ///
+    /// Set by the `write_timeout` URL query parameter, in seconds. Defaults to
+    /// waiting indefinitely.
+    pub write_timeout: Option<Duration>,
+    /// Socket options for TCP connections.
+    pub tcp: TcpOptions,
+    /// Additional servers to try, in order, if a connection to `target`
+    /// cannot be established.
+    ///
+    /// Set by listing multiple comma-separated hosts in the URL, as in
+    /// `postgres://host1:5432,host2:5433/database`.
+    pub fallback_targets: Vec<(ConnectTarget, Option<u16>)>,
+    /// If set, servers are tried in a random order rather than the order in
+    /// which they were specified.
+    ///
+    /// Set by the `load_balance_hosts=random` URL query parameter. Defaults to
+    /// `false`.
+    pub load_balance_hosts: bool,
+    /// The properties a session must have for the connection to a server to
+    /// be accepted.
+    ///
+    /// Set by the `target_session_attrs` URL query parameter. Defaults to
+    /// `TargetSessionAttrs::Any`.
+    pub target_session_attrs: TargetSessionAttrs,
+    /// SSL configuration for the connection.
+    pub ssl: SslOptions,
+}
+
+/// SSL configuration included with the connection parameters.
+///
+/// Each option can be set by the URL query parameter given in its
+/// documentation.
+#[derive(Clone, Debug, Default)]
+pub struct SslOptions {
+    /// The SSL mode to use, set by `sslmode`.
+    ///
+    /// If set and `SslMode::None` is passed to `Connection::connect`, this
+    /// mode is used with an OpenSSL `SslContext` configured by the other
+    /// options. Modes other than `Disable` require the `with-openssl`
+    /// feature. Otherwise, the explicit `SslMode` is used, and connecting
+    /// fails if it is a different mode.
+    pub mode: Option<SslModeKind>,
+    /// A file containing trusted certificate authorities in PEM format, set
+    /// by `sslrootcert`.
+    ///
+    /// Defaults to the system's trusted certificate authorities.
+    pub root_cert: Option<PathBuf>,
+    /// A file containing a client certificate in PEM format, set by
+    /// `sslcert`.
+    pub cert: Option<PathBuf>,
+    /// A file containing the private key for the client certificate in PEM
+    /// format, set by `sslkey`.
+    pub key: Option<PathBuf>,
+}
+
+/// The SSL modes which can be specified by connection parameters.
+///
+/// They correspond to the variants of `SslMode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SslModeKind {
+    /// `disable`
+    Disable,
+    /// `prefer`
+    Prefer,
+    /// `require`
+    Require,
+    /// `verify-ca`
+    VerifyCa,
+    /// `verify-full`
+    VerifyFull,
+}
+
+/// Properties required of a session, used to select between multiple
+/// servers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TargetSessionAttrs {
+    /// Any session is acceptable.
+    Any,
+    /// The session must allow writes, as determined by
+    /// `transaction_read_only`.
+    ReadWrite,
+    /// The session must not allow writes, as determined by
+    /// `transaction_read_only`.
+    ReadOnly,
+    /// The server must not be in hot standby mode, as determined by
+    /// `pg_is_in_recovery()`.
+    Primary,
+    /// The server must be in hot standby mode, as determined by
+    /// `pg_is_in_recovery()`.
+    Standby,
+}
+
+/// Socket options applied to TCP connections.
+///
+/// Each option can be set by the URL query parameter of the same name.
+/// Durations are specified in seconds.
+#[derive(Clone, Debug)]
+pub struct TcpOptions {
+    /// Enables TCP keepalives, so that connections to a server which has
+    /// gone away are detected even while idle.
+    ///
+    /// Defaults to `true`.
+    pub keepalives: bool,
+    /// The amount of time a connection must be idle before keepalive probes
+    /// are sent.
+    ///
+    /// Defaults to the operating system's setting.
+    pub keepalives_idle: Option<Duration>,
+    /// The amount of time between keepalive probes. Ignored on platforms
+    /// other than Linux and Android.
+    ///
+    /// Defaults to the operating system's setting.
+    pub keepalives_interval: Option<Duration>,
+    /// The number of unacknowledged keepalive probes after which the
+    /// connection is considered dead. Ignored on platforms other than Linux
+    /// and Android.
+    ///
+    /// Defaults to the operating system's setting.
+    pub keepalives_count: Option<u32>,
+    /// Disables Nagle's algorithm, so that small messages are sent
+    /// immediately.
+    ///
+    /// Defaults to `true`.
+    pub tcp_nodelay: bool,
+    /// The size of the socket's send buffer in bytes.
+    ///
+    /// Defaults to the operating system's setting.
+    pub send_buffer_size: Option<usize>,
+    /// The size of the socket's receive buffer in bytes.
+    ///
+    /// Defaults to the operating system's setting.
+    pub recv_buffer_size: Option<usize>,
+}
+
+impl Default for TcpOptions {
+    fn default() -> TcpOptions {
+        TcpOptions {
+            keepalives: true,
+            keepalives_idle: None,
+            keepalives_interval: None,
+            keepalives_count: None,
+            tcp_nodelay: true,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+        }
+    }
 }
 
 /// A trait implemented by types that can be converted into a `ConnectParams`.
@@ -158,11 +340,25 @@ impl IntoConnectParams for ConnectParams {
     }
 }
 
+/// Strings starting with `postgres://` or `postgresql://` are parsed as URLs.
+/// Anything else is parsed as a libpq-style `key=value` connection string,
+/// such as `host=localhost user=postgres dbname='my db'`.
+///
+/// In both cases, parameters which are not specified are taken from the
+/// `PGHOST`, `PGPORT`, `PGUSER`, `PGPASSWORD`, `PGDATABASE` and `PGAPPNAME`
+/// environment variables if they are set.
 impl<'a> IntoConnectParams for &'a str {
     fn into_connect_params(self) -> result::Result<ConnectParams, ConnectError> {
-        match Url::parse(self) {
-            Ok(url) => url.into_connect_params(),
-            Err(err) => return Err(ConnectError::InvalidUrl(err)),
+        if self.starts_with("postgres://") || self.starts_with("postgresql://") {
+            match Url::parse(self) {
+                Ok(url) => url.into_connect_params(),
+                Err(err) => return Err(ConnectError::InvalidUrl(err)),
+            }
+        } else {
+            match dsn::parse(self) {
+                Ok(pairs) => ConnectParams::from_pairs(pairs),
+                Err(err) => Err(ConnectError::InvalidUrl(err)),
+            }
         }
     }
 }
@@ -170,29 +366,36 @@ impl<'a> IntoConnectParams for &'a str {
 impl IntoConnectParams for Url {
     fn into_connect_params(self) -> result::Result<ConnectParams, ConnectError> {
         let Url {
-            host,
-            port,
+            hosts,
             user,
-            path: url::Path { mut path, query: options, .. },
+            path: url::Path { mut path, query, .. },
             ..
         } = self;
 
-        #[cfg(feature = "unix_socket")]
-        fn make_unix(maybe_path: String) -> result::Result<ConnectTarget, ConnectError> {
-            Ok(ConnectTarget::Unix(PathBuf::from(&maybe_path)))
-        }
-        #[cfg(not(feature = "unix_socket"))]
-        fn make_unix(_: String) -> result::Result<ConnectTarget, ConnectError> {
-            Err(ConnectError::InvalidUrl("unix socket support requires the `unix_socket` feature"
-                                         .to_string()))
-        }
-
-        let maybe_path = try!(url::decode_component(&host).map_err(ConnectError::InvalidUrl));
-        let target = if maybe_path.starts_with("/") {
-            try!(make_unix(maybe_path))
+        let mut targets = if hosts.len() == 1 && hosts[0].host.is_empty() {
+            try!(parse_targets(&env_var("PGHOST").unwrap_or("localhost".to_owned()),
+                               hosts[0].port.map(|p| p.to_string()).or(env_var("PGPORT"))))
         } else {
-            ConnectTarget::Tcp(host)
+            let default_port = match env_var("PGPORT") {
+                Some(port) => Some(try!(parse_value("PGPORT", &port))),
+                None => None,
+            };
+
+            let mut targets = vec![];
+            for url::Host { host, port } in hosts {
+                let maybe_path = try!(url::decode_component(&host)
+                                          .map_err(ConnectError::InvalidUrl));
+                let target = if maybe_path.starts_with("/") {
+                    try!(make_unix(maybe_path))
+                } else {
+                    ConnectTarget::Tcp(host)
+                };
+                targets.push((target, port.or(default_port)));
+            }
+            targets
         };
+        // There is always at least one host
+        let (target, port) = targets.remove(0);
 
         let user = user.map(|url::UserInfo { user, pass }| {
             UserInfo { user: user, password: pass }
@@ -206,13 +409,313 @@ impl IntoConnectParams for Url {
             Some(path)
         };
 
-        Ok(ConnectParams {
+        let mut params = ConnectParams {
             target: target,
             port: port,
             user: user,
             database: database,
-            options: options,
-        })
+            options: vec![],
+            connect_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+            tcp: TcpOptions::default(),
+            fallback_targets: targets,
+            load_balance_hosts: false,
+            target_session_attrs: TargetSessionAttrs::Any,
+            ssl: SslOptions::default(),
+        };
+
+        for (key, value) in query {
+            try!(params.set_param(key, value));
+        }
+        try!(params.apply_env_defaults());
+
+        Ok(params)
+    }
+}
+
+#[cfg(feature = "unix_socket")]
+fn make_unix(maybe_path: String) -> result::Result<ConnectTarget, ConnectError> {
+    Ok(ConnectTarget::Unix(PathBuf::from(&maybe_path)))
+}
+#[cfg(not(feature = "unix_socket"))]
+fn make_unix(_: String) -> result::Result<ConnectTarget, ConnectError> {
+    Err(ConnectError::InvalidUrl("unix socket support requires the `unix_socket` feature"
+                                 .to_string()))
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok()
+}
+
+// Parses libpq-style comma-separated lists of hosts and ports. Hosts starting
+// with `/` are Unix socket directories. A single port applies to every host.
+fn parse_targets(hosts: &str, ports: Option<String>)
+                 -> result::Result<Vec<(ConnectTarget, Option<u16>)>, ConnectError> {
+    let hosts = hosts.split(',').collect::<Vec<_>>();
+    let mut parsed_ports = vec![];
+    if let Some(ref ports) = ports {
+        for port in ports.split(',') {
+            parsed_ports.push(Some(try!(parse_value::<u16>("port", port))));
+        }
+    }
+
+    let ports = match parsed_ports.len() {
+        0 => vec![None; hosts.len()],
+        1 => vec![parsed_ports[0]; hosts.len()],
+        n if n == hosts.len() => parsed_ports,
+        _ => {
+            return Err(ConnectError::InvalidUrl("the number of ports does not match the \
+                                                 number of hosts".to_owned()))
+        }
+    };
+
+    let mut targets = vec![];
+    for (host, port) in hosts.into_iter().zip(ports) {
+        let target = if host.starts_with("/") {
+            try!(make_unix(host.to_owned()))
+        } else {
+            ConnectTarget::Tcp(host.to_owned())
+        };
+        targets.push((target, port));
+    }
+    Ok(targets)
+}
+
+impl ConnectParams {
+    // Builds parameters from the key/value pairs of a libpq-style connection
+    // string.
+    fn from_pairs(pairs: Vec<(String, String)>) -> result::Result<ConnectParams, ConnectError> {
+        let mut host = None;
+        let mut port = None;
+        let mut user = None;
+        let mut password = None;
+        let mut database = None;
+        let mut fallback_application_name = None;
+        let mut rest = vec![];
+
+        for (key, value) in pairs {
+            match &*key {
+                "host" => host = Some(value),
+                "port" => port = Some(value),
+                "user" => user = Some(value),
+                "password" => password = Some(value),
+                "dbname" => database = Some(value),
+                "fallback_application_name" => fallback_application_name = Some(value),
+                _ => rest.push((key, value)),
+            }
+        }
+
+        let host = host.or_else(|| env_var("PGHOST")).unwrap_or("localhost".to_owned());
+        let mut targets = try!(parse_targets(&host, port.or_else(|| env_var("PGPORT"))));
+        let (target, port) = targets.remove(0);
+
+        let mut params = ConnectParams {
+            target: target,
+            port: port,
+            user: user.map(|user| UserInfo { user: user, password: None }),
+            database: database,
+            options: vec![],
+            connect_timeout: None,
+            read_timeout: None,
+            write_timeout: None,
+            tcp: TcpOptions::default(),
+            fallback_targets: targets,
+            load_balance_hosts: false,
+            target_session_attrs: TargetSessionAttrs::Any,
+            ssl: SslOptions::default(),
+        };
+
+        for (key, value) in rest {
+            try!(params.set_param(key, value));
+        }
+        try!(params.apply_env_defaults());
+
+        // The password applies even if the user came from the environment
+        if let Some(password) = password {
+            if let Some(ref mut user) = params.user {
+                user.password = Some(password);
+            }
+        }
+
+        if let Some(name) = fallback_application_name {
+            if !params.options.iter().any(|&(ref k, _)| k == "application_name") {
+                params.options.push(("application_name".to_owned(), name));
+            }
+        }
+
+        Ok(params)
+    }
+
+    // Fills in the user, password, database and application name from the
+    // libpq environment variables if they weren't specified.
+    fn apply_env_defaults(&mut self) -> result::Result<(), ConnectError> {
+        if self.user.is_none() {
+            if let Some(user) = env_var("PGUSER") {
+                self.user = Some(UserInfo { user: user, password: None });
+            }
+        }
+
+        if let Some(ref mut user) = self.user {
+            if user.password.is_none() {
+                user.password = env_var("PGPASSWORD");
+            }
+        }
+
+        if self.database.is_none() {
+            self.database = env_var("PGDATABASE");
+        }
+
+        if !self.options.iter().any(|&(ref k, _)| k == "application_name") {
+            if let Some(name) = env_var("PGAPPNAME") {
+                try!(self.set_param("application_name".to_owned(), name));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Handles a connection parameter, passing unrecognized ones on to the
+    // backend as runtime parameters.
+    fn set_param(&mut self, key: String, value: String) -> result::Result<(), ConnectError> {
+        let handled = match &*key {
+            "connect_timeout" => {
+                self.connect_timeout = try!(parse_timeout(&key, &value));
+                true
+            }
+            "read_timeout" => {
+                self.read_timeout = try!(parse_timeout(&key, &value));
+                true
+            }
+            "write_timeout" => {
+                self.write_timeout = try!(parse_timeout(&key, &value));
+                true
+            }
+            "keepalives" => {
+                self.tcp.keepalives = try!(parse_bool(&key, &value));
+                true
+            }
+            "keepalives_idle" => {
+                self.tcp.keepalives_idle = try!(parse_timeout(&key, &value));
+                true
+            }
+            "keepalives_interval" => {
+                self.tcp.keepalives_interval = try!(parse_timeout(&key, &value));
+                true
+            }
+            "keepalives_count" => {
+                self.tcp.keepalives_count = Some(try!(parse_value(&key, &value)));
+                true
+            }
+            "tcp_nodelay" => {
+                self.tcp.tcp_nodelay = try!(parse_bool(&key, &value));
+                true
+            }
+            "send_buffer_size" => {
+                self.tcp.send_buffer_size = Some(try!(parse_value(&key, &value)));
+                true
+            }
+            "recv_buffer_size" => {
+                self.tcp.recv_buffer_size = Some(try!(parse_value(&key, &value)));
+                true
+            }
+            "load_balance_hosts" => {
+                self.load_balance_hosts = match &*value {
+                    "disable" => false,
+                    "random" => true,
+                    _ => return invalid_param(&key, &value),
+                };
+                true
+            }
+            "target_session_attrs" => {
+                self.target_session_attrs = match &*value {
+                    "any" => TargetSessionAttrs::Any,
+                    "read-write" => TargetSessionAttrs::ReadWrite,
+                    "read-only" => TargetSessionAttrs::ReadOnly,
+                    "primary" => TargetSessionAttrs::Primary,
+                    "standby" => TargetSessionAttrs::Standby,
+                    _ => return invalid_param(&key, &value),
+                };
+                true
+            }
+            "sslmode" => {
+                self.ssl.mode = match &*value {
+                    "disable" => Some(SslModeKind::Disable),
+                    "prefer" => Some(SslModeKind::Prefer),
+                    "require" => Some(SslModeKind::Require),
+                    "verify-ca" => Some(SslModeKind::VerifyCa),
+                    "verify-full" => Some(SslModeKind::VerifyFull),
+                    _ => return invalid_param(&key, &value),
+                };
+                true
+            }
+            "sslrootcert" => {
+                self.ssl.root_cert = Some(PathBuf::from(value.clone()));
+                true
+            }
+            "sslcert" => {
+                self.ssl.cert = Some(PathBuf::from(value.clone()));
+                true
+            }
+            "sslkey" => {
+                self.ssl.key = Some(PathBuf::from(value.clone()));
+                true
+            }
+            _ => false,
+        };
+
+        if !handled {
+            self.options.push((key, value));
+        }
+        Ok(())
+    }
+
+    // Splits the parameters into one set for each server to try, in the order
+    // they should be tried.
+    fn per_target(&self) -> Vec<ConnectParams> {
+        let mut targets = vec![(self.target.clone(), self.port)];
+        targets.extend(self.fallback_targets.iter().cloned());
+
+        if self.load_balance_hosts {
+            rand::thread_rng().shuffle(&mut targets);
+        }
+
+        targets.into_iter().map(|(target, port)| {
+            ConnectParams {
+                target: target,
+                port: port,
+                fallback_targets: vec![],
+                ..self.clone()
+            }
+        }).collect()
+    }
+}
+
+fn invalid_param<T>(key: &str, value: &str) -> result::Result<T, ConnectError> {
+    Err(ConnectError::InvalidUrl(format!("invalid value for {}: {}", key, value)))
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> result::Result<T, ConnectError> {
+    match value.parse() {
+        Ok(value) => Ok(value),
+        Err(_) => invalid_param(key, value),
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> result::Result<bool, ConnectError> {
+    match value {
+        "1" | "true" | "on" => Ok(true),
+        "0" | "false" | "off" => Ok(false),
+        _ => invalid_param(key, value),
+    }
+}
+
+// Timeouts are specified in whole seconds, with 0 meaning no timeout, as in
+// libpq.
+fn parse_timeout(key: &str, value: &str) -> result::Result<Option<Duration>, ConnectError> {
+    match try!(parse_value::<u64>(key, value)) {
+        0 => Ok(None),
+        secs => Ok(Some(Duration::from_secs(secs))),
     }
 }
 
@@ -295,23 +798,19 @@ impl<'conn> Notifications<'conn> {
         }
     }
 
-    /*
-    /// Returns the oldest pending notification
+    /// Returns the oldest pending notification.
     ///
     /// If no notifications are pending, blocks for up to `timeout` time, after
-    /// which `None` is returned.
+    /// which `None` is returned. The connection remains usable after a
+    /// timeout.
     ///
     /// ## Example
     ///
     /// ```rust,no_run
-    /// # #![allow(unstable)]
-    /// use std::old_io::{IoError, IoErrorKind};
     /// use std::time::Duration;
     ///
-    /// use postgres::Error;
-    ///
     /// # let conn = postgres::Connection::connect("", &postgres::SslMode::None).unwrap();
-    /// match conn.notifications().next_block_for(Duration::seconds(2)) {
+    /// match conn.notifications().next_block_for(Duration::from_secs(2)) {
     ///     Some(Ok(notification)) => println!("notification: {}", notification.payload),
     ///     Some(Err(e)) => println!("Error: {:?}", e),
     ///     None => println!("Wait for notification timed out"),
@@ -327,29 +826,34 @@ impl<'conn> Notifications<'conn> {
             return Some(Err(Error::StreamDesynchronized));
         }
 
-        let end = SteadyTime::now() + timeout;
-        loop {
-            let timeout = max(Duration::zero(), end - SteadyTime::now()).num_milliseconds() as u64;
-            conn.stream.set_read_timeout(Some(timeout));
-            match conn.read_one_message() {
-                Ok(Some(NotificationResponse { pid, channel, payload })) => {
-                    return Some(Ok(Notification {
-                        pid: pid,
-                        channel: channel,
-                        payload: payload
-                    }))
-                }
-                Ok(Some(_)) => unreachable!(),
-                Ok(None) => {}
-                Err(IoError { kind: IoErrorKind::TimedOut, .. }) => {
-                    conn.desynchronized = false;
-                    return None;
-                }
-                Err(e) => return Some(Err(Error::IoError(e))),
-            }
+        match conn.read_notification_until(Some(Instant::now() + timeout)) {
+            Ok(Some(notification)) => Some(Ok(notification)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    /// Returns the oldest pending notification without blocking.
+    ///
+    /// Unlike `next`, this also processes any data which has already been
+    /// received from the server, so notifications sent while the connection
+    /// was idle are returned.
+    pub fn try_next(&mut self) -> Option<Result<Notification>> {
+        if let Some(notification) = self.next() {
+            return Some(Ok(notification));
+        }
+
+        let mut conn = self.conn.conn.borrow_mut();
+        if conn.desynchronized {
+            return Some(Err(Error::StreamDesynchronized));
+        }
+
+        match conn.read_notification_until(None) {
+            Ok(Some(notification)) => Some(Ok(notification)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
         }
     }
-    */
 }
 
 /// Contains information necessary to cancel queries for a session.
@@ -390,7 +894,29 @@ pub struct CancelData {
 pub fn cancel_query<T>(params: T, ssl: &SslMode, data: CancelData)
                        -> result::Result<(), ConnectError> where T: IntoConnectParams {
     let params = try!(params.into_connect_params());
-    let mut socket = try!(io_util::initialize_stream(&params, ssl));
+
+    // We don't know which server the connection ended up on, but servers
+    // ignore cancellation requests for processes they don't own, so the
+    // request is sent to all of them.
+    let mut result = Ok(());
+    let mut sent = false;
+    for params in params.per_target() {
+        match send_cancel_request(&params, ssl, data) {
+            Ok(()) => sent = true,
+            Err(err) => result = Err(err),
+        }
+    }
+
+    if sent {
+        Ok(())
+    } else {
+        result
+    }
+}
+
+fn send_cancel_request(params: &ConnectParams, ssl: &SslMode, data: CancelData)
+                       -> result::Result<(), ConnectError> {
+    let mut socket = try!(io_util::initialize_stream(params, ssl));
 
     try!(socket.write_message(&CancelRequest {
         code: message::CANCEL_CODE,
@@ -464,7 +990,7 @@ struct CachedStatement {
 }
 
 struct InnerConnection {
-    stream: BufStream<MaybeSslStream<InternalStream>>,
+    stream: BufStream<GatedStream>,
     notice_handler: Box<HandleNotice>,
     notifications: VecDeque<Notification>,
     cancel_data: CancelData,
@@ -475,6 +1001,7 @@ struct InnerConnection {
     trans_depth: u32,
     desynchronized: bool,
     finished: bool,
+    read_timeout: Option<Duration>,
 }
 
 impl Drop for InnerConnection {
@@ -489,14 +1016,51 @@ impl InnerConnection {
     fn connect<T>(params: T, ssl: &SslMode) -> result::Result<InnerConnection, ConnectError>
             where T: IntoConnectParams {
         let params = try!(params.into_connect_params());
+
+        let mut last_err = None;
+        for params in params.per_target() {
+            let target = params.target.clone();
+            match InnerConnection::connect_target(params, ssl) {
+                Ok(conn) => return Ok(conn),
+                Err(err) => {
+                    debug!("unable to connect to {:?}: {}", target, err);
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        // There is always at least one target
+        Err(last_err.unwrap())
+    }
+
+    fn connect_target(params: ConnectParams, ssl: &SslMode)
+                      -> result::Result<InnerConnection, ConnectError> {
         let stream = try!(io_util::initialize_stream(&params, ssl));
 
-        let ConnectParams { user, database, mut options, .. } = params;
+        // Unix socket connections are listed as localhost in the password
+        // file
+        let pgpass_host = match params.target {
+            ConnectTarget::Tcp(ref host) => host.clone(),
+            #[cfg(feature = "unix_socket")]
+            ConnectTarget::Unix(_) => "localhost".to_owned(),
+        };
+        let pgpass_port = params.port.unwrap_or(io_util::DEFAULT_PORT);
+
+        let ConnectParams {
+            user,
+            database,
+            mut options,
+            read_timeout,
+            target_session_attrs,
+            ..
+        } = params;
 
         let user = try!(user.ok_or(ConnectError::MissingUser));
+        let pgpass_user = user.user.clone();
+        let pgpass_database = database.clone().unwrap_or_else(|| user.user.clone());
 
         let mut conn = InnerConnection {
-            stream: BufStream::new(stream),
+            stream: BufStream::new(GatedStream::new(stream)),
             next_stmt_id: 0,
             notice_handler: Box::new(LoggingNoticeHandler),
             notifications: VecDeque::new(),
@@ -507,11 +1071,12 @@ impl InnerConnection {
             desynchronized: false,
             finished: false,
             trans_depth: 0,
+            read_timeout: read_timeout,
         };
 
         options.push(("client_encoding".to_owned(), "UTF8".to_owned()));
         // Postgres uses the value of TimeZone as the time zone for TIMESTAMP
-        // WITH TIME ZONE values. Timespec converts to GMT internally.
+        // WITH TIME ZONE values. Timespec and chrono's types convert to GMT internally.
         options.push(("TimeZone".to_owned(), "GMT".to_owned()));
         // We have to clone here since we need the user again for auth
         options.push(("user".to_owned(), user.user.clone()));
@@ -524,7 +1089,9 @@ impl InnerConnection {
             parameters: &options
         }]));
 
-        try!(conn.handle_auth(user));
+        try!(conn.handle_auth(user, || {
+            pgpass::find_password(&pgpass_host, pgpass_port, &pgpass_database, &pgpass_user)
+        }));
 
         loop {
             match try!(conn.read_message()) {
@@ -539,19 +1106,47 @@ impl InnerConnection {
         }
 
         try!(conn.setup_typeinfo_query());
+        try!(conn.setup_typeinfo_enum_query());
+        try!(conn.check_session_attrs(target_session_attrs));
 
         Ok(conn)
     }
 
+    fn check_session_attrs(&mut self, attrs: TargetSessionAttrs)
+                           -> result::Result<(), ConnectError> {
+        let (query, expected) = match attrs {
+            TargetSessionAttrs::Any => return Ok(()),
+            TargetSessionAttrs::ReadWrite => ("SHOW transaction_read_only", "off"),
+            TargetSessionAttrs::ReadOnly => ("SHOW transaction_read_only", "on"),
+            TargetSessionAttrs::Primary => ("SELECT pg_is_in_recovery()", "f"),
+            TargetSessionAttrs::Standby => ("SELECT pg_is_in_recovery()", "t"),
+        };
+
+        let rows = match self.quick_query(query) {
+            Ok(rows) => rows,
+            Err(Error::IoError(e)) => return Err(ConnectError::IoError(e)),
+            Err(Error::Timeout) => return Err(ConnectError::Timeout),
+            Err(Error::DbError(e)) => return Err(ConnectError::DbError(e)),
+            Err(_) => return Err(ConnectError::BadResponse),
+        };
+
+        match rows.get(0).and_then(|row| row.get(0)).and_then(|value| value.as_ref()) {
+            Some(value) if value == expected => Ok(()),
+            Some(_) => Err(ConnectError::WrongSessionAttrs(attrs)),
+            None => Err(ConnectError::BadResponse),
+        }
+    }
+
     fn setup_typeinfo_query(&mut self) -> result::Result<(), ConnectError> {
         match self.raw_prepare(TYPEINFO_QUERY,
-                               "SELECT t.typname, t.typelem, r.rngsubtype \
+                               "SELECT t.typname, t.typtype, t.typelem, r.rngsubtype \
                                 FROM pg_catalog.pg_type t \
                                 LEFT OUTER JOIN pg_catalog.pg_range r \
                                     ON r.rngtypid = t.oid \
                                 WHERE t.oid = $1") {
             Ok(..) => return Ok(()),
             Err(Error::IoError(e)) => return Err(ConnectError::IoError(e)),
+            Err(Error::Timeout) => return Err(ConnectError::Timeout),
             // Range types weren't added until Postgres 9.2, so pg_range may not exist
             Err(Error::DbError(ref e)) if e.code() == &SqlState::UndefinedTable => {}
             Err(Error::DbError(e)) => return Err(ConnectError::DbError(e)),
@@ -559,11 +1154,40 @@ impl InnerConnection {
         }
 
         match self.raw_prepare(TYPEINFO_QUERY,
-                               "SELECT typname, typelem, NULL::OID \
+                               "SELECT typname, typtype, typelem, NULL::OID \
                                 FROM pg_catalog.pg_type \
                                 WHERE oid = $1") {
             Ok(..) => Ok(()),
             Err(Error::IoError(e)) => Err(ConnectError::IoError(e)),
+            Err(Error::Timeout) => Err(ConnectError::Timeout),
+            Err(Error::DbError(e)) => Err(ConnectError::DbError(e)),
+            _ => unreachable!()
+        }
+    }
+
+    fn setup_typeinfo_enum_query(&mut self) -> result::Result<(), ConnectError> {
+        match self.raw_prepare(TYPEINFO_ENUM_QUERY,
+                               "SELECT enumlabel \
+                                FROM pg_catalog.pg_enum \
+                                WHERE enumtypid = $1 \
+                                ORDER BY enumsortorder") {
+            Ok(..) => return Ok(()),
+            Err(Error::IoError(e)) => return Err(ConnectError::IoError(e)),
+            Err(Error::Timeout) => return Err(ConnectError::Timeout),
+            // enumsortorder wasn't added until Postgres 9.1
+            Err(Error::DbError(ref e)) if e.code() == &SqlState::UndefinedColumn => {}
+            Err(Error::DbError(e)) => return Err(ConnectError::DbError(e)),
+            _ => unreachable!()
+        }
+
+        match self.raw_prepare(TYPEINFO_ENUM_QUERY,
+                               "SELECT enumlabel \
+                                FROM pg_catalog.pg_enum \
+                                WHERE enumtypid = $1 \
+                                ORDER BY oid") {
+            Ok(..) => Ok(()),
+            Err(Error::IoError(e)) => Err(ConnectError::IoError(e)),
+            Err(Error::Timeout) => Err(ConnectError::Timeout),
             Err(Error::DbError(e)) => Err(ConnectError::DbError(e)),
             _ => unreachable!()
         }
@@ -594,6 +1218,61 @@ impl InnerConnection {
         }
     }
 
+    // Waits for data to be readable without consuming any of it. If `timeout`
+    // is `None`, only data which has already arrived is considered. Returns
+    // `false` if nothing arrived in time.
+    fn wait_for_data(&mut self, timeout: Option<Duration>) -> io::Result<bool> {
+        // Data may already be buffered by the `BufStream` or the SSL session,
+        // and neither is read from here
+        self.stream.get_mut().set_open(false);
+        let buffered = self.stream.fill_buf().is_ok();
+        self.stream.get_mut().set_open(true);
+
+        let wrapper = self.stream.get_ref().get_ref();
+        if buffered || wrapper.pending() > 0 {
+            return Ok(true);
+        }
+
+        io_util::internal(wrapper.get_ref()).wait_readable(timeout, self.read_timeout)
+    }
+
+    // Reads notifications until one arrives or `deadline` passes. If
+    // `deadline` is `None`, only data which has already arrived is read. The
+    // timeout only applies while waiting for the start of a message, so the
+    // stream never desynchronizes because of it.
+    fn read_notification_until(&mut self, deadline: Option<Instant>)
+                               -> Result<Option<Notification>> {
+        loop {
+            let timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now < deadline {
+                        Some(deadline - now)
+                    } else {
+                        None
+                    }
+                }
+                None => None,
+            };
+
+            if !try_desync!(self, self.wait_for_data(timeout)) {
+                return Ok(None);
+            }
+
+            match try!(self.read_one_message()) {
+                Some(NotificationResponse { pid, channel, payload }) => {
+                    return Ok(Some(Notification {
+                        pid: pid,
+                        channel: channel,
+                        payload: payload
+                    }))
+                }
+                Some(_) => bad_response!(self),
+                None => {}
+            }
+        }
+    }
+
     fn read_message_with_notification(&mut self) -> io::Result<BackendMessage> {
         loop {
             if let Some(msg) = try!(self.read_one_message()) {
@@ -617,28 +1296,61 @@ impl InnerConnection {
         }
     }
 
-    fn handle_auth(&mut self, user: UserInfo) -> result::Result<(), ConnectError> {
+    // If the server asks for a password and none was provided, `pgpass` is
+    // called to look one up.
+    fn handle_auth<F>(&mut self, user: UserInfo, pgpass: F) -> result::Result<(), ConnectError>
+            where F: FnOnce() -> Option<String> {
         match try!(self.read_message()) {
             AuthenticationOk => return Ok(()),
             AuthenticationCleartextPassword => {
-                let pass = try!(user.password.ok_or(ConnectError::MissingPassword));
+                let pass = try!(user.password.or_else(pgpass)
+                                    .ok_or(ConnectError::MissingPassword));
                 try!(self.write_messages(&[PasswordMessage {
                         password: &pass,
                     }]));
             }
             AuthenticationMD5Password { salt } => {
-                let pass = try!(user.password.ok_or(ConnectError::MissingPassword));
-                let mut hasher = Hasher::new(hash::Type::MD5);
-                let _ = hasher.write_all(pass.as_bytes());
-                let _ = hasher.write_all(user.user.as_bytes());
-                let output = hasher.finish().to_hex();
-                let _ = hasher.write_all(output.as_bytes());
-                let _ = hasher.write_all(&salt);
-                let output = format!("md5{}", hasher.finish().to_hex());
+                let pass = try!(user.password.or_else(pgpass)
+                                    .ok_or(ConnectError::MissingPassword));
+                let mut hasher = md5::Context::new();
+                hasher.consume(pass.as_bytes());
+                hasher.consume(user.user.as_bytes());
+                let output = format!("{:x}", hasher.compute());
+                let mut hasher = md5::Context::new();
+                hasher.consume(output.as_bytes());
+                hasher.consume(&salt);
+                let output = format!("md5{:x}", hasher.compute());
                 try!(self.write_messages(&[PasswordMessage {
                         password: &output
                     }]));
             }
+            AuthenticationSASL { mechanisms } => {
+                if !mechanisms.iter().any(|m| m == scram::SCRAM_SHA_256) {
+                    return Err(ConnectError::UnsupportedAuthentication);
+                }
+                let pass = try!(user.password.or_else(pgpass)
+                                    .ok_or(ConnectError::MissingPassword));
+                let mut scram = try!(ScramSha256::new(pass.as_bytes()));
+                try!(self.write_messages(&[SASLInitialResponse {
+                        mechanism: scram::SCRAM_SHA_256,
+                        data: scram.message(),
+                    }]));
+
+                match try!(self.read_message()) {
+                    AuthenticationSASLContinue { data } => try!(scram.update(&data)),
+                    ErrorResponse { fields } => return ugh_privacy::dberror_new_connect(fields),
+                    _ => return Err(ConnectError::BadResponse),
+                }
+                try!(self.write_messages(&[SASLResponse {
+                        data: scram.message(),
+                    }]));
+
+                match try!(self.read_message()) {
+                    AuthenticationSASLFinal { data } => try!(scram.finish(&data)),
+                    ErrorResponse { fields } => return ugh_privacy::dberror_new_connect(fields),
+                    _ => return Err(ConnectError::BadResponse),
+                }
+            }
             AuthenticationKerberosV5
             | AuthenticationSCMCredential
             | AuthenticationGSS
@@ -813,42 +1525,18 @@ impl InnerConnection {
             return Ok(ty.clone());
         }
 
-        // Ew @ doing this manually :(
-        let mut buf = vec![];
-        let value = match try!(oid.to_sql_checked(&Type::Oid, &mut buf)) {
-            IsNull::Yes => None,
-            IsNull::No => Some(buf),
-        };
-        try!(self.write_messages(&[
-            Bind {
-                portal: "",
-                statement: TYPEINFO_QUERY,
-                formats: &[1],
-                values: &[value],
-                result_formats: &[1]
-            },
-            Execute {
-                portal: "",
-                max_rows: 0,
-            },
-            Sync]));
-        match try!(self.read_message()) {
-            BindComplete => {}
-            ErrorResponse { fields } => {
-                try!(self.wait_for_ready());
-                return ugh_privacy::dberror_new(fields);
-            }
-            _ => bad_response!(self)
-        }
-        let (name, elem_oid, rngsubtype): (String, Oid, Option<Oid>) =
+        try!(self.execute_typeinfo_query(TYPEINFO_QUERY, oid));
+        let (name, typtype, elem_oid, rngsubtype): (String, i8, Oid, Option<Oid>) =
                 match try!(self.read_message()) {
             DataRow { row } => {
                 (try!(FromSql::from_sql_nullable(&Type::Name,
                                                  row[0].as_ref().map(|r| &**r).as_mut())),
-                 try!(FromSql::from_sql_nullable(&Type::Oid,
+                 try!(FromSql::from_sql_nullable(&Type::Char,
                                                  row[1].as_ref().map(|r| &**r).as_mut())),
                  try!(FromSql::from_sql_nullable(&Type::Oid,
-                                                 row[2].as_ref().map(|r| &**r).as_mut())))
+                                                 row[2].as_ref().map(|r| &**r).as_mut())),
+                 try!(FromSql::from_sql_nullable(&Type::Oid,
+                                                 row[3].as_ref().map(|r| &**r).as_mut())))
             }
             ErrorResponse { fields } => {
                 try!(self.wait_for_ready());
@@ -866,7 +1554,9 @@ impl InnerConnection {
         }
         try!(self.wait_for_ready());
 
-        let kind = if elem_oid != 0 {
+        let kind = if typtype == b'e' as i8 {
+            Kind::Enum(try!(self.read_enum_labels(oid)))
+        } else if elem_oid != 0 {
             Kind::Array(try!(self.get_type(elem_oid)))
         } else {
             match rngsubtype {
@@ -880,6 +1570,49 @@ impl InnerConnection {
         Ok(type_)
     }
 
+    // Ew @ doing this manually :(
+    fn execute_typeinfo_query(&mut self, statement: &str, oid: Oid) -> Result<()> {
+        let mut buf = vec![];
+        let value = match try!(oid.to_sql_checked(&Type::Oid, &mut buf)) {
+            IsNull::Yes => None,
+            IsNull::No => Some(buf),
+        };
+        try!(self.write_messages(&[
+            Bind {
+                portal: "",
+                statement: statement,
+                formats: &[1],
+                values: &[value],
+                result_formats: &[1]
+            },
+            Execute {
+                portal: "",
+                max_rows: 0,
+            },
+            Sync]));
+        match try!(self.read_message()) {
+            BindComplete => Ok(()),
+            ErrorResponse { fields } => {
+                try!(self.wait_for_ready());
+                ugh_privacy::dberror_new(fields)
+            }
+            _ => bad_response!(self)
+        }
+    }
+
+    fn read_enum_labels(&mut self, oid: Oid) -> Result<Vec<String>> {
+        try!(self.execute_typeinfo_query(TYPEINFO_ENUM_QUERY, oid));
+        let mut rows = VecDeque::new();
+        try!(read_rows(self, &mut rows));
+
+        let mut labels = vec![];
+        for row in rows {
+            labels.push(try!(FromSql::from_sql_nullable(&Type::Name,
+                                                        row[0].as_ref().map(|r| &**r).as_mut())));
+        }
+        Ok(labels)
+    }
+
     fn is_desynchronized(&self) -> bool {
         self.desynchronized
     }
@@ -891,6 +1624,18 @@ impl InnerConnection {
         }
     }
 
+    // There's no way to abort a COPY TO STDOUT from the client side, so the
+    // data is read and thrown away.
+    fn discard_copy_out<T>(&mut self) -> Result<T> {
+        loop {
+            if let ReadyForQuery { .. } = try!(self.read_message()) {
+                break;
+            }
+        }
+        Err(Error::IoError(io::Error::new(io::ErrorKind::InvalidInput,
+                                          "COPY TO STDOUT queries must be executed with `copy_out`")))
+    }
+
     fn quick_query(&mut self, query: &str) -> Result<Vec<Vec<Option<String>>>> {
         check_desync!(self);
         debug!("executing query: {}", query);
@@ -912,6 +1657,7 @@ impl InnerConnection {
                         },
                         Sync]));
                 }
+                CopyOutResponse { .. } => return self.discard_copy_out(),
                 ErrorResponse { fields } => {
                     try!(self.wait_for_ready());
                     return ugh_privacy::dberror_new(fields);
@@ -988,7 +1734,8 @@ impl Connection {
     ///
     /// ```rust,no_run
     /// # #![allow(unstable)]
-    /// # use postgres::{Connection, UserInfo, ConnectParams, SslMode, ConnectTarget, ConnectError};
+    /// # use postgres::{Connection, UserInfo, ConnectParams, SslMode, ConnectTarget, ConnectError,
+    /// #                TcpOptions, TargetSessionAttrs, SslOptions};
     /// # #[cfg(feature = "unix_socket")]
     /// # fn f() -> Result<(), ConnectError> {
     /// # let some_crazy_path = Path::new("");
@@ -1001,6 +1748,14 @@ impl Connection {
     ///     }),
     ///     database: None,
     ///     options: vec![],
+    ///     connect_timeout: None,
+    ///     read_timeout: None,
+    ///     write_timeout: None,
+    ///     tcp: TcpOptions::default(),
+    ///     fallback_targets: vec![],
+    ///     load_balance_hosts: false,
+    ///     target_session_attrs: TargetSessionAttrs::Any,
+    ///     ssl: SslOptions::default(),
     /// };
     /// let conn = try!(Connection::connect(params, &SslMode::None));
     /// # Ok(()) };
@@ -1146,18 +1901,79 @@ impl Connection {
         IsolationLevel::parse(result[0][0].as_ref().unwrap())
     }
 
-    /// A convenience function for queries that are only run once.
+    /// A convenience function for queries that are only run once.
+    ///
+    /// If an error is returned, it could have come from either the preparation
+    /// or execution of the statement.
+    ///
+    /// On success, returns the number of rows modified or 0 if not applicable.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number
+    /// expected.
+    pub fn execute(&self, query: &str, params: &[&ToSql]) -> Result<u64> {
+        let (param_types, columns) = try!(self.conn.borrow_mut().raw_prepare("", query));
+        let stmt = Statement {
+            conn: self,
+            name: "".to_owned(),
+            param_types: param_types,
+            columns: columns,
+            next_portal_id: Cell::new(0),
+            finished: true, // << !!
+        };
+        stmt.execute(params)
+    }
+
+    /// A convenience function for `COPY TO STDOUT` queries that are only run
+    /// once.
+    ///
+    /// The data sent by the server is written to `w` as it arrives. Text,
+    /// CSV and binary formats are all supported, as specified by the query.
+    ///
+    /// Returns the number of rows copied.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// # use postgres::{Connection, SslMode};
+    /// # use std::io;
+    /// # let conn = Connection::connect("", &SslMode::None).unwrap();
+    /// let stdout = io::stdout();
+    /// conn.copy_out("COPY foo TO STDOUT WITH (FORMAT csv, HEADER)", &mut stdout.lock()).unwrap();
+    /// ```
+    pub fn copy_out<W: Write>(&self, query: &str, w: &mut W) -> Result<u64> {
+        let (param_types, columns) = try!(self.conn.borrow_mut().raw_prepare("", query));
+        let stmt = Statement {
+            conn: self,
+            name: "".to_owned(),
+            param_types: param_types,
+            columns: columns,
+            next_portal_id: Cell::new(0),
+            finished: true, // << !!
+        };
+        stmt.copy_out(&[], w)
+    }
+
+    /// A convenience function for `COPY FROM STDIN` queries that are only run
+    /// once.
     ///
-    /// If an error is returned, it could have come from either the preparation
-    /// or execution of the statement.
+    /// The contents of `r` are streamed to the server without modification,
+    /// so any format and options supported by `COPY` may be used.
     ///
-    /// On success, returns the number of rows modified or 0 if not applicable.
+    /// Returns the number of rows copied.
     ///
-    /// ## Panics
+    /// ## Example
     ///
-    /// Panics if the number of parameters provided does not match the number
-    /// expected.
-    pub fn execute(&self, query: &str, params: &[&ToSql]) -> Result<u64> {
+    /// ```rust,no_run
+    /// # use postgres::{Connection, SslMode};
+    /// # use std::fs::File;
+    /// # let conn = Connection::connect("", &SslMode::None).unwrap();
+    /// let mut file = File::open("people.csv").unwrap();
+    /// conn.copy_in("COPY person (id, name) FROM STDIN WITH (FORMAT csv, HEADER)",
+    ///              &mut file).unwrap();
+    /// ```
+    pub fn copy_in<R: Read>(&self, query: &str, r: &mut R) -> Result<u64> {
         let (param_types, columns) = try!(self.conn.borrow_mut().raw_prepare("", query));
         let stmt = Statement {
             conn: self,
@@ -1167,7 +1983,7 @@ impl Connection {
             next_portal_id: Cell::new(0),
             finished: true, // << !!
         };
-        stmt.execute(params)
+        stmt.copy_in(&[], r)
     }
 
     /// Execute a sequence of SQL statements.
@@ -1257,9 +2073,16 @@ pub enum SslMode {
     /// The connection will not use SSL.
     None,
     /// The connection will use SSL if the backend supports it.
-    Prefer(SslContext),
+    Prefer(Box<NegotiateSsl>),
     /// The connection must use SSL.
-    Require(SslContext)
+    Require(Box<NegotiateSsl>),
+    /// The connection must use SSL, and the server's certificate must be
+    /// signed by a certificate authority trusted by the negotiator.
+    VerifyCa(Box<NegotiateSsl>),
+    /// The connection must use SSL, the server's certificate must be signed
+    /// by a certificate authority trusted by the negotiator, and the
+    /// certificate must match the host name of the server.
+    VerifyFull(Box<NegotiateSsl>),
 }
 
 /// Represents a transaction on a database connection.
@@ -1326,6 +2149,16 @@ impl<'conn> Transaction<'conn> {
         self.conn.execute(query, params)
     }
 
+    /// Like `Connection::copy_out`.
+    pub fn copy_out<W: Write>(&self, query: &str, w: &mut W) -> Result<u64> {
+        self.conn.copy_out(query, w)
+    }
+
+    /// Like `Connection::copy_in`.
+    pub fn copy_in<R: Read>(&self, query: &str, r: &mut R) -> Result<u64> {
+        self.conn.copy_in(query, r)
+    }
+
     /// Like `Connection::batch_execute`.
     pub fn batch_execute(&self, query: &str) -> Result<()> {
         self.conn.batch_execute(query)
@@ -1542,6 +2375,7 @@ impl<'conn> Statement<'conn> {
                         },
                         Sync]));
                 }
+                CopyOutResponse { .. } => return conn.discard_copy_out(),
                 _ => {
                     conn.desynchronized = true;
                     return Err(Error::BadResponse);
@@ -1635,6 +2469,197 @@ impl<'conn> Statement<'conn> {
         })
     }
 
+    /// Executes a `COPY TO STDOUT` statement, returning a reader over the
+    /// data sent by the server.
+    ///
+    /// The data is streamed from the server as it is read, so arbitrarily
+    /// large results can be processed without being buffered in memory. The
+    /// connection may not be used for anything else until the reader has
+    /// been consumed or dropped.
+    ///
+    /// Returns an error if the statement is not a `COPY TO STDOUT` statement.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number
+    /// expected.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// # use postgres::{Connection, SslMode};
+    /// # use std::io::prelude::*;
+    /// # let conn = Connection::connect("", &SslMode::None).unwrap();
+    /// let stmt = conn.prepare("COPY foo TO STDOUT").unwrap();
+    /// let mut reader = stmt.copy_out_reader(&[]).unwrap();
+    /// let mut line = String::new();
+    /// while reader.read_line(&mut line).unwrap() != 0 {
+    ///     println!("{}", line.trim_right());
+    ///     line.clear();
+    /// }
+    /// let count = reader.finish().unwrap();
+    /// ```
+    pub fn copy_out_reader<'a>(&'a self, params: &[&ToSql]) -> Result<CopyOutReader<'a>> {
+        check_desync!(self.conn);
+        try!(self.inner_execute("", 0, params));
+
+        let mut conn = self.conn.conn.borrow_mut();
+        let (format, column_formats) = match try!(conn.read_message()) {
+            CopyOutResponse { format, column_formats } => (format, column_formats),
+            CopyInResponse { .. } => {
+                try!(conn.write_messages(&[
+                    CopyFail {
+                        message: "COPY FROM STDIN queries cannot be executed with `copy_out`",
+                    },
+                    Sync]));
+                match try!(conn.read_message()) {
+                    ErrorResponse { fields } => {
+                        try!(conn.wait_for_ready());
+                        return ugh_privacy::dberror_new(fields);
+                    }
+                    _ => bad_response!(conn),
+                }
+            }
+            ErrorResponse { fields } => {
+                try!(conn.wait_for_ready());
+                return ugh_privacy::dberror_new(fields);
+            }
+            _ => {
+                loop {
+                    if let ReadyForQuery { .. } = try!(conn.read_message()) {
+                        break;
+                    }
+                }
+                return Err(Error::IoError(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "called `copy_out` on a statement which is not a `COPY TO STDOUT` \
+                         statement")));
+            }
+        };
+
+        Ok(CopyOutReader {
+            conn: self.conn,
+            format: Format::from_u16(format as u16),
+            column_formats: column_formats.into_iter().map(Format::from_u16).collect(),
+            buf: vec![],
+            pos: 0,
+            count: 0,
+            done: false,
+            error: None,
+            finished: false,
+        })
+    }
+
+    /// Executes a `COPY TO STDOUT` statement, writing the resulting data to
+    /// `w`.
+    ///
+    /// Returns the number of rows copied.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number
+    /// expected.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// # use postgres::{Connection, SslMode};
+    /// # use std::fs::File;
+    /// # let conn = Connection::connect("", &SslMode::None).unwrap();
+    /// let mut file = File::create("foo.csv").unwrap();
+    /// let stmt = conn.prepare("COPY foo TO STDOUT WITH (FORMAT csv)").unwrap();
+    /// let count = stmt.copy_out(&[], &mut file).unwrap();
+    /// println!("exported {} rows", count);
+    /// ```
+    pub fn copy_out<W: Write>(&self, params: &[&ToSql], w: &mut W) -> Result<u64> {
+        let mut reader = try!(self.copy_out_reader(params));
+        match io::copy(&mut reader, w) {
+            Ok(_) => reader.finish(),
+            // If the error came from the connection, `finish` will report it.
+            // Otherwise it came from `w` and we still need to drain the rest
+            // of the data from the connection.
+            Err(err) => reader.finish().and_then(|_| Err(Error::IoError(err))),
+        }
+    }
+
+    /// Executes a `COPY FROM STDIN` statement, streaming the data read from
+    /// `r` to the server.
+    ///
+    /// The data is sent as-is, so it must be in the format specified by the
+    /// statement, whether that's text, CSV or binary.
+    ///
+    /// Returns the number of rows copied.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the number of parameters provided does not match the number
+    /// expected.
+    pub fn copy_in<R: Read>(&self, params: &[&ToSql], r: &mut R) -> Result<u64> {
+        check_desync!(self.conn);
+        try!(self.inner_execute("", 0, params));
+
+        let mut conn = self.conn.conn.borrow_mut();
+        match try!(conn.read_message()) {
+            CopyInResponse { .. } => {}
+            CopyOutResponse { .. } => return conn.discard_copy_out(),
+            ErrorResponse { fields } => {
+                try!(conn.wait_for_ready());
+                return ugh_privacy::dberror_new(fields);
+            }
+            _ => {
+                loop {
+                    if let ReadyForQuery { .. } = try!(conn.read_message()) {
+                        break;
+                    }
+                }
+                return Err(Error::IoError(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "called `copy_in` on a statement which is not a `COPY FROM STDIN` \
+                         statement")));
+            }
+        }
+
+        let mut buf = vec![0; COPY_IN_CHUNK_SIZE];
+        loop {
+            match r.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => {
+                    try_desync!(conn, conn.stream.write_message(
+                        &CopyData {
+                            data: &buf[..len],
+                        }));
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    try!(conn.write_messages(&[
+                        CopyFail {
+                            message: "Error reading input",
+                        },
+                        Sync]));
+                    match try!(conn.read_message()) {
+                        ErrorResponse { .. } => try!(conn.wait_for_ready()),
+                        _ => bad_response!(conn),
+                    }
+                    return Err(Error::IoError(err));
+                }
+            }
+        }
+
+        try!(conn.write_messages(&[CopyDone, Sync]));
+
+        let num = match try!(conn.read_message()) {
+            CommandComplete { tag } => util::parse_update_count(tag),
+            ErrorResponse { fields } => {
+                try!(conn.wait_for_ready());
+                return ugh_privacy::dberror_new(fields);
+            }
+            _ => bad_response!(conn),
+        };
+
+        try!(conn.wait_for_ready());
+        Ok(num)
+    }
+
     /// Consumes the statement, clearing it from the Postgres session.
     ///
     /// If this statement was created via the `prepare_cached` method, `finish`
@@ -1690,6 +2715,7 @@ fn read_rows(conn: &mut InnerConnection, buf: &mut VecDeque<Vec<Option<Vec<u8>>>
                     },
                     Sync]));
             }
+            CopyOutResponse { .. } => return conn.discard_copy_out(),
             _ => {
                 conn.desynchronized = true;
                 return Err(Error::BadResponse);
@@ -2105,10 +3131,29 @@ impl<'a> CopyInStatement<'a> {
     /// designed to allow callers to avoid having to maintain the entire row
     /// set in memory.
     ///
+    /// If a value cannot be converted to its column's type or a row does not
+    /// contain the expected number of values, the `COPY` is aborted and an
+    /// `Error::CopyInConversion` or `Error::CopyInColumnCount` identifying the
+    /// offending row is returned. No rows are inserted in that case.
+    ///
     /// Returns the number of rows copied.
     pub fn execute<I, J>(&self, rows: I) -> Result<u64>
             where I: Iterator<Item=J>, J: StreamIterator {
+        self.try_execute(rows.map(|row| -> result::Result<J, io::Error> { Ok(row) }))
+    }
+
+    /// Like `execute`, except that the caller may abort the `COPY` by having
+    /// `rows` return an error.
+    ///
+    /// When `rows` returns an error, the `COPY` is cancelled, no rows are
+    /// inserted, and the error is returned as `Error::CopyInAborted`. The
+    /// connection remains usable afterwards.
+    pub fn try_execute<I, J, E>(&self, rows: I) -> Result<u64>
+            where I: Iterator<Item=result::Result<J, E>>,
+                  J: StreamIterator,
+                  E: Into<Box<StdError+Sync+Send>> {
         let mut conn = self.conn.conn.borrow_mut();
+        check_desync!(conn);
 
         debug!("executing COPY IN statement {}", self.name);
         try!(conn.write_messages(&[
@@ -2145,18 +3190,63 @@ impl<'a> CopyInStatement<'a> {
             }
         }
 
+        if let Err(err) = self.write_rows(&mut conn, rows) {
+            // An IO error means we can't talk to the server anymore
+            if conn.desynchronized {
+                return Err(err);
+            }
+
+            try!(conn.write_messages(&[
+                CopyFail {
+                    message: &err.to_string(),
+                },
+                Sync]));
+            match try!(conn.read_message()) {
+                ErrorResponse { .. } => try!(conn.wait_for_ready()),
+                _ => bad_response!(conn),
+            }
+            return Err(err);
+        }
+
+        try!(conn.write_messages(&[CopyDone, Sync]));
+
+        let num = match try!(conn.read_message()) {
+            CommandComplete { tag } => util::parse_update_count(tag),
+            ErrorResponse { fields } => {
+                try!(conn.wait_for_ready());
+                return ugh_privacy::dberror_new(fields);
+            }
+            _ => {
+                conn.desynchronized = true;
+                return Err(Error::BadResponse);
+            }
+        };
+
+        try!(conn.wait_for_ready());
+        Ok(num)
+    }
+
+    fn write_rows<I, J, E>(&self, conn: &mut InnerConnection, rows: I) -> Result<()>
+            where I: Iterator<Item=result::Result<J, E>>,
+                  J: StreamIterator,
+                  E: Into<Box<StdError+Sync+Send>> {
         let mut buf = vec![];
         let _ = buf.write_all(b"PGCOPY\n\xff\r\n\x00");
         let _ = buf.write_i32::<BigEndian>(0);
         let _ = buf.write_i32::<BigEndian>(0);
 
-        'l: for mut row in rows {
+        for (row_idx, row) in rows.enumerate() {
+            let mut row = match row {
+                Ok(row) => row,
+                Err(err) => return Err(Error::CopyInAborted(err.into())),
+            };
+
             let _ = buf.write_i16::<BigEndian>(self.column_types.len() as i16);
 
-            let mut types = self.column_types.iter();
+            let mut types = self.column_types.iter().enumerate();
             loop {
                 match (row.next(), types.next()) {
-                    (Some(val), Some(ty)) => {
+                    (Some(val), Some((column_idx, ty))) => {
                         let mut inner_buf = vec![];
                         match val.to_sql_checked(ty, &mut inner_buf) {
                             Ok(IsNull::Yes) => {
@@ -2167,21 +3257,19 @@ impl<'a> CopyInStatement<'a> {
                                 let _ = buf.write_all(&inner_buf);
                             }
                             Err(err) => {
-                                // FIXME this is not the right way to handle this
-                                try_desync!(conn, conn.stream.write_message(
-                                    &CopyFail {
-                                        message: &err.to_string(),
-                                    }));
-                                break 'l;
+                                return Err(Error::CopyInConversion {
+                                    row: row_idx,
+                                    column: column_idx,
+                                    error: Box::new(err),
+                                });
                             }
                         }
                     }
                     (Some(_), None) | (None, Some(_)) => {
-                        try_desync!(conn, conn.stream.write_message(
-                            &CopyFail {
-                                message: "Invalid column count",
-                            }));
-                        break 'l;
+                        return Err(Error::CopyInColumnCount {
+                            row: row_idx,
+                            expected: self.column_types.len(),
+                        });
                     }
                     (None, None) => break
                 }
@@ -2195,40 +3283,176 @@ impl<'a> CopyInStatement<'a> {
         }
 
         let _ = buf.write_i16::<BigEndian>(-1);
-        try!(conn.write_messages(&[
-            CopyData {
+        try_desync!(conn, conn.stream.write_message(
+            &CopyData {
                 data: &buf,
-            },
-            CopyDone,
-            Sync]));
+            }));
+        Ok(())
+    }
 
-        let num = match try!(conn.read_message()) {
-            CommandComplete { tag } => util::parse_update_count(tag),
+    /// Consumes the statement, clearing it from the Postgres session.
+    ///
+    /// Functionally identical to the `Drop` implementation of the
+    /// `CopyInStatement` except that it returns any error to the
+    /// caller.
+    pub fn finish(mut self) -> Result<()> {
+        self.finished = true;
+        self.finish_inner()
+    }
+}
+
+/// The format of data in a `COPY` operation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Textual data, as in the `text` and `csv` formats.
+    Text,
+    /// Binary data.
+    Binary,
+}
+
+impl Format {
+    fn from_u16(value: u16) -> Format {
+        if value == 0 {
+            Format::Text
+        } else {
+            Format::Binary
+        }
+    }
+}
+
+/// A reader over the data produced by a `COPY TO STDOUT` statement.
+///
+/// The connection is not available for other use until the reader has been
+/// consumed. Any data left unread when the reader is dropped is discarded.
+pub struct CopyOutReader<'a> {
+    conn: &'a Connection,
+    format: Format,
+    column_formats: Vec<Format>,
+    buf: Vec<u8>,
+    pos: usize,
+    count: u64,
+    done: bool,
+    error: Option<Error>,
+    finished: bool,
+}
+
+impl<'a> fmt::Debug for CopyOutReader<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        DebugStruct::new(fmt, "CopyOutReader")
+            .field("format", &self.format)
+            .field("column_formats", &self.column_formats)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<'a> Drop for CopyOutReader<'a> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.finish_inner();
+        }
+    }
+}
+
+impl<'a> CopyOutReader<'a> {
+    fn read_message(&mut self) -> Result<()> {
+        let mut conn = self.conn.conn.borrow_mut();
+        let message = match conn.read_message() {
+            Ok(message) => message,
+            Err(err) => {
+                self.done = true;
+                return Err(From::from(err));
+            }
+        };
+
+        match message {
+            BCopyData { data } => {
+                self.buf = data;
+                self.pos = 0;
+            }
+            BCopyDone => {}
+            CommandComplete { tag } => {
+                self.done = true;
+                self.count = util::parse_update_count(tag);
+                try!(conn.wait_for_ready());
+            }
             ErrorResponse { fields } => {
+                self.done = true;
                 try!(conn.wait_for_ready());
                 return ugh_privacy::dberror_new(fields);
             }
             _ => {
-                conn.desynchronized = true;
-                return Err(Error::BadResponse);
+                self.done = true;
+                bad_response!(conn);
             }
-        };
+        }
 
-        try!(conn.wait_for_ready());
-        Ok(num)
+        Ok(())
     }
 
-    /// Consumes the statement, clearing it from the Postgres session.
+    fn finish_inner(&mut self) -> Result<u64> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        while !self.done {
+            try!(self.read_message());
+        }
+        Ok(self.count)
+    }
+
+    /// Returns the overall format of the data.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the format of each column of the data.
+    pub fn column_formats(&self) -> &[Format] {
+        &self.column_formats
+    }
+
+    /// Consumes the reader, discarding any unread data.
     ///
-    /// Functionally identical to the `Drop` implementation of the
-    /// `CopyInStatement` except that it returns any error to the
-    /// caller.
-    pub fn finish(mut self) -> Result<()> {
+    /// Returns the number of rows copied, or the first error encountered
+    /// while reading.
+    pub fn finish(mut self) -> Result<u64> {
         self.finished = true;
         self.finish_inner()
     }
 }
 
+impl<'a> Read for CopyOutReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let mut data = try!(self.fill_buf());
+            try!(data.read(buf))
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<'a> BufRead for CopyOutReader<'a> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pos == self.buf.len() && !self.done {
+            if let Err(err) = self.read_message() {
+                // The full error is reported by `finish`
+                let io_err = match err {
+                    Error::IoError(ref err) => io::Error::new(err.kind(), err.to_string()),
+                    ref err => io::Error::new(io::ErrorKind::Other, err.to_string()),
+                };
+                self.error = Some(err);
+                return Err(io_err);
+            }
+        }
+
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.buf.len());
+    }
+}
+
 /// A trait allowing abstraction over connections and transactions
 pub trait GenericConnection {
     /// Like `Connection::prepare`.
@@ -21,12 +21,25 @@ pub enum BackendMessage {
         salt: [u8; 4]
     },
     AuthenticationOk,
+    AuthenticationSASL {
+        mechanisms: Vec<String>
+    },
+    AuthenticationSASLContinue {
+        data: Vec<u8>
+    },
+    AuthenticationSASLFinal {
+        data: Vec<u8>
+    },
     AuthenticationSCMCredential,
     AuthenticationSSPI,
     BackendKeyData {
         process_id: u32,
         secret_key: u32
     },
+    BCopyData {
+        data: Vec<u8>,
+    },
+    BCopyDone,
     BindComplete,
     CloseComplete,
     CommandComplete {
@@ -36,6 +49,10 @@ pub enum BackendMessage {
         format: u8,
         column_formats: Vec<u16>,
     },
+    CopyOutResponse {
+        format: u8,
+        column_formats: Vec<u16>,
+    },
     DataRow {
         row: Vec<Option<Vec<u8>>>
     },
@@ -122,6 +139,13 @@ pub enum FrontendMessage<'a> {
     Query {
         query: &'a str
     },
+    SASLInitialResponse {
+        mechanism: &'a str,
+        data: &'a [u8]
+    },
+    SASLResponse {
+        data: &'a [u8]
+    },
     SslRequest {
         code: u32
     },
@@ -228,6 +252,16 @@ impl<W: Write> WriteMessage for W {
                 ident = Some(b'Q');
                 try!(buf.write_cstr(query));
             }
+            SASLInitialResponse { mechanism, data } => {
+                ident = Some(b'p');
+                try!(buf.write_cstr(mechanism));
+                try!(buf.write_i32::<BigEndian>(data.len() as i32));
+                try!(buf.write_all(data));
+            }
+            SASLResponse { data } => {
+                ident = Some(b'p');
+                try!(buf.write_all(data));
+            }
             StartupMessage { version, parameters } => {
                 try!(buf.write_u32::<BigEndian>(version));
                 for &(ref k, ref v) in parameters {
@@ -289,7 +323,13 @@ impl<R: BufRead> ReadMessage for R {
                 channel: try!(rdr.read_cstr()),
                 payload: try!(rdr.read_cstr())
             },
+            b'c' => BCopyDone,
             b'C' => CommandComplete { tag: try!(rdr.read_cstr()) },
+            b'd' => {
+                let mut data = vec![];
+                try!(rdr.read_to_end(&mut data));
+                BCopyData { data: data }
+            }
             b'D' => try!(read_data_row(&mut rdr)),
             b'E' => ErrorResponse { fields: try!(read_fields(&mut rdr)) },
             b'G' => {
@@ -303,6 +343,17 @@ impl<R: BufRead> ReadMessage for R {
                     column_formats: column_formats,
                 }
             }
+            b'H' => {
+                let format = try!(rdr.read_u8());
+                let mut column_formats = vec![];
+                for _ in 0..try!(rdr.read_u16::<BigEndian>()) {
+                    column_formats.push(try!(rdr.read_u16::<BigEndian>()));
+                }
+                CopyOutResponse {
+                    format: format,
+                    column_formats: column_formats,
+                }
+            }
             b'I' => EmptyQueryResponse,
             b'K' => BackendKeyData {
                 process_id: try!(rdr.read_u32::<BigEndian>()),
@@ -361,7 +412,7 @@ fn read_data_row<R: BufRead>(buf: &mut R) -> io::Result<BackendMessage> {
     Ok(DataRow { row: values })
 }
 
-fn read_auth_message<R: Read>(buf: &mut R) -> io::Result<BackendMessage> {
+fn read_auth_message<R: BufRead>(buf: &mut R) -> io::Result<BackendMessage> {
     Ok(match try!(buf.read_i32::<BigEndian>()) {
         0 => AuthenticationOk,
         2 => AuthenticationKerberosV5,
@@ -374,6 +425,27 @@ fn read_auth_message<R: Read>(buf: &mut R) -> io::Result<BackendMessage> {
         6 => AuthenticationSCMCredential,
         7 => AuthenticationGSS,
         9 => AuthenticationSSPI,
+        10 => {
+            let mut mechanisms = vec![];
+            loop {
+                let mechanism = try!(buf.read_cstr());
+                if mechanism.is_empty() {
+                    break;
+                }
+                mechanisms.push(mechanism);
+            }
+            AuthenticationSASL { mechanisms: mechanisms }
+        }
+        11 => {
+            let mut data = vec![];
+            try!(buf.read_to_end(&mut data));
+            AuthenticationSASLContinue { data: data }
+        }
+        12 => {
+            let mut data = vec![];
+            try!(buf.read_to_end(&mut data));
+            AuthenticationSASLFinal { data: data }
+        }
         _ => return Err(io::Error::new(io::ErrorKind::Other, "unexpected authentication tag")),
     })
 }
@@ -0,0 +1,133 @@
+// Password lookup in the libpq password file.
+//
+// The file is named by `PGPASSFILE`, defaulting to `~/.pgpass`, or
+// `%APPDATA%\postgresql\pgpass.conf` on Windows. Each line has the form
+// `hostname:port:database:username:password`. Any of the first four fields
+// may be `*` to match anything, and `\` escapes `:` and `\` within a field.
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::prelude::*;
+use std::mem;
+use std::path::PathBuf;
+
+#[cfg(unix)]
+fn default_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".pgpass"))
+}
+
+#[cfg(windows)]
+fn default_path() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("postgresql").join("pgpass.conf"))
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &PathBuf, file: &File) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = match file.metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => return false,
+    };
+
+    if !metadata.is_file() {
+        warn!("password file {:?} is not a plain file", path);
+        return false;
+    }
+
+    // Like libpq, ignore the file if anyone but the owner can access it
+    if metadata.permissions().mode() & 0o077 != 0 {
+        warn!("password file {:?} has group or world access; permissions should be u=rw (0600) \
+               or less",
+              path);
+        return false;
+    }
+
+    true
+}
+
+#[cfg(windows)]
+fn check_permissions(_: &PathBuf, file: &File) -> bool {
+    file.metadata().map(|m| m.is_file()).unwrap_or(false)
+}
+
+// Splits a line into its five fields, unescaping each. Lines without enough
+// fields are skipped.
+fn parse_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    field.push(c);
+                }
+            }
+            // The password is the remainder of the line
+            ':' if fields.len() < 4 => fields.push(mem::replace(&mut field, String::new())),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    if fields.len() == 5 {
+        Some(fields)
+    } else {
+        None
+    }
+}
+
+/// Returns the password from the first line of the password file matching
+/// the connection, if there is one.
+pub fn find_password(host: &str, port: u16, database: &str, user: &str) -> Option<String> {
+    let path = match env::var_os("PGPASSFILE") {
+        Some(path) => PathBuf::from(path),
+        None => match default_path() {
+            Some(path) => path,
+            None => return None,
+        },
+    };
+
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_) => return None,
+    };
+
+    if !check_permissions(&path, &file) {
+        return None;
+    }
+
+    let port = port.to_string();
+    let wanted = [host, &*port, database, user];
+
+    for line in BufReader::new(file).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                warn!("error reading password file {:?}: {}", path, err);
+                return None;
+            }
+        };
+
+        let line = line.trim_right_matches('\r');
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = match parse_line(line) {
+            Some(fields) => fields,
+            None => continue,
+        };
+
+        let matches = fields.iter()
+                            .zip(wanted.iter())
+                            .all(|(field, wanted)| field == "*" || field == wanted);
+        if matches {
+            return fields.pop();
+        }
+    }
+
+    None
+}
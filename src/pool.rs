@@ -0,0 +1,462 @@
+//! A thread-safe pool of connections.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use postgres::SslMode;
+//! use postgres::pool::{Pool, Config};
+//! use std::thread;
+//!
+//! let pool = Pool::new("postgres://postgres@localhost", SslMode::None, Config::default())
+//!     .unwrap();
+//!
+//! for i in 0..10i32 {
+//!     let pool = pool.clone();
+//!     thread::spawn(move || {
+//!         let conn = pool.get().unwrap();
+//!         conn.execute("INSERT INTO foo (bar) VALUES ($1)", &[&i]).unwrap();
+//!     });
+//! }
+//! ```
+use debug_builders::DebugStruct;
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+use std::result;
+use std::sync::{Arc, Weak, Mutex, Condvar};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use {Result,
+     Connection,
+     ConnectParams,
+     ConnectError,
+     IntoConnectParams,
+     SslMode,
+     GenericConnection,
+     Statement,
+     CopyInStatement,
+     Transaction,
+     ToSql};
+
+const REAPER_INTERVAL_SECS: u64 = 30;
+
+/// Configuration for a `Pool`.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The number of connections the pool will try to keep open at all
+    /// times.
+    ///
+    /// Defaults to 0.
+    pub min_size: u32,
+    /// The maximum number of connections the pool will open at once.
+    ///
+    /// Defaults to 10.
+    pub max_size: u32,
+    /// The amount of time `Pool::get` will wait for a connection to become
+    /// available before returning an error.
+    ///
+    /// Defaults to 30 seconds.
+    pub checkout_timeout: Duration,
+    /// Connections which have sat idle in the pool for longer than this are
+    /// closed, as long as doing so doesn't drop the pool below `min_size`
+    /// connections.
+    ///
+    /// Defaults to 10 minutes.
+    pub idle_timeout: Option<Duration>,
+    /// Connections which have been open for longer than this are closed
+    /// rather than being reused.
+    ///
+    /// Defaults to 30 minutes.
+    pub max_lifetime: Option<Duration>,
+    /// If set, a trivial query is run on connections before they are handed
+    /// out, and broken connections are discarded.
+    ///
+    /// Defaults to `true`.
+    pub test_on_checkout: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            min_size: 0,
+            max_size: 10,
+            checkout_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+            test_on_checkout: true,
+        }
+    }
+}
+
+/// Reasons a connection could not be retrieved from a `Pool`.
+#[derive(Debug)]
+pub enum PoolError {
+    /// No connection became available before the checkout timeout expired.
+    Timeout,
+    /// A new connection could not be opened.
+    ConnectError(ConnectError),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(fmt.write_str(error::Error::description(self)));
+        match *self {
+            PoolError::ConnectError(ref err) => write!(fmt, ": {}", err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl error::Error for PoolError {
+    fn description(&self) -> &str {
+        match *self {
+            PoolError::Timeout => "Timed out waiting for a connection",
+            PoolError::ConnectError(_) => "Error opening a new connection",
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            PoolError::ConnectError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConnectError> for PoolError {
+    fn from(err: ConnectError) -> PoolError {
+        PoolError::ConnectError(err)
+    }
+}
+
+/// A snapshot of the state of a `Pool`.
+#[derive(Copy, Clone, Debug)]
+pub struct State {
+    /// The number of connections currently open, whether idle or in use.
+    pub connections: u32,
+    /// The number of idle connections.
+    pub idle_connections: u32,
+}
+
+struct IdleConnection {
+    conn: Connection,
+    created: Instant,
+    idle_since: Instant,
+}
+
+struct Internals {
+    idle: VecDeque<IdleConnection>,
+    num_conns: u32,
+}
+
+struct PoolInner {
+    params: ConnectParams,
+    ssl: SslMode,
+    config: Config,
+    internals: Mutex<Internals>,
+    cond: Condvar,
+}
+
+impl PoolInner {
+    fn connect(&self) -> result::Result<IdleConnection, ConnectError> {
+        let conn = try!(Connection::connect(self.params.clone(), &self.ssl));
+        let now = Instant::now();
+        Ok(IdleConnection {
+            conn: conn,
+            created: now,
+            idle_since: now,
+        })
+    }
+
+    fn expired(&self, conn: &IdleConnection, now: Instant) -> bool {
+        match self.config.max_lifetime {
+            Some(lifetime) => now.duration_since(conn.created) >= lifetime,
+            None => false,
+        }
+    }
+
+    fn is_healthy(&self, conn: &IdleConnection) -> bool {
+        if conn.conn.is_desynchronized() || self.expired(conn, Instant::now()) {
+            return false;
+        }
+
+        !self.config.test_on_checkout || conn.conn.batch_execute("").is_ok()
+    }
+
+    // Opens connections until there are at least `min_size` of them.
+    fn replenish(&self) -> result::Result<(), ConnectError> {
+        loop {
+            {
+                let mut internals = self.internals.lock().unwrap();
+                if internals.num_conns >= self.config.min_size {
+                    return Ok(());
+                }
+                internals.num_conns += 1;
+            }
+
+            let result = self.connect();
+            let mut internals = self.internals.lock().unwrap();
+            match result {
+                Ok(conn) => {
+                    internals.idle.push_back(conn);
+                    self.cond.notify_one();
+                }
+                Err(err) => {
+                    internals.num_conns -= 1;
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    fn reap(&self) {
+        let now = Instant::now();
+        let mut closed = vec![];
+
+        {
+            let mut internals = self.internals.lock().unwrap();
+            let idle = mem::replace(&mut internals.idle, VecDeque::new());
+            for conn in idle {
+                let idle_expired = match self.config.idle_timeout {
+                    Some(timeout) => {
+                        now.duration_since(conn.idle_since) >= timeout
+                            && internals.num_conns > self.config.min_size
+                    }
+                    None => false,
+                };
+
+                if idle_expired || self.expired(&conn, now) {
+                    internals.num_conns -= 1;
+                    closed.push(conn);
+                } else {
+                    internals.idle.push_back(conn);
+                }
+            }
+        }
+
+        // Close connections without holding the lock
+        drop(closed);
+        let _ = self.replenish();
+    }
+
+    fn put_back(&self, mut conn: IdleConnection) {
+        let keep = !conn.conn.is_desynchronized() && !self.expired(&conn, Instant::now());
+
+        let closed = {
+            let mut internals = self.internals.lock().unwrap();
+            let closed = if keep {
+                conn.idle_since = Instant::now();
+                internals.idle.push_back(conn);
+                None
+            } else {
+                internals.num_conns -= 1;
+                Some(conn)
+            };
+            self.cond.notify_one();
+            closed
+        };
+
+        drop(closed);
+    }
+}
+
+fn reaper(pool: Weak<PoolInner>) {
+    loop {
+        thread::sleep(Duration::from_secs(REAPER_INTERVAL_SECS));
+        match pool.upgrade() {
+            Some(pool) => pool.reap(),
+            None => break,
+        }
+    }
+}
+
+/// A thread-safe pool of connections.
+///
+/// `Pool` is a cheap handle to the underlying pool; clones refer to the same
+/// set of connections.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<PoolInner>,
+}
+
+impl fmt::Debug for Pool {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state();
+        DebugStruct::new(fmt, "Pool")
+            .field("connections", &state.connections)
+            .field("idle_connections", &state.idle_connections)
+            .field("config", &self.inner.config)
+            .finish()
+    }
+}
+
+impl Pool {
+    /// Creates a new pool, opening `config.min_size` connections up front.
+    ///
+    /// See `Connection::connect` for details of the `params` argument.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `config.max_size` is 0 or is less than `config.min_size`.
+    pub fn new<T>(params: T, ssl: SslMode, config: Config) -> result::Result<Pool, ConnectError>
+            where T: IntoConnectParams {
+        assert!(config.max_size > 0, "max_size must be positive");
+        assert!(config.min_size <= config.max_size, "min_size must not exceed max_size");
+
+        let params = try!(params.into_connect_params());
+        let inner = Arc::new(PoolInner {
+            params: params,
+            ssl: ssl,
+            config: config,
+            internals: Mutex::new(Internals {
+                idle: VecDeque::new(),
+                num_conns: 0,
+            }),
+            cond: Condvar::new(),
+        });
+
+        try!(inner.replenish());
+
+        let weak = Arc::downgrade(&inner);
+        thread::spawn(move || reaper(weak));
+
+        Ok(Pool { inner: inner })
+    }
+
+    /// Retrieves a connection from the pool.
+    ///
+    /// An idle connection is reused if one is available. Otherwise a new
+    /// connection is opened if the pool is below its maximum size, or the
+    /// call waits up to the pool's checkout timeout for a connection to be
+    /// returned.
+    pub fn get(&self) -> result::Result<PooledConnection, PoolError> {
+        let deadline = Instant::now() + self.inner.config.checkout_timeout;
+        let mut internals = self.inner.internals.lock().unwrap();
+
+        loop {
+            if let Some(conn) = internals.idle.pop_back() {
+                drop(internals);
+                if self.inner.is_healthy(&conn) {
+                    return Ok(PooledConnection {
+                        pool: self.inner.clone(),
+                        conn: Some(conn),
+                    });
+                }
+                drop(conn);
+
+                internals = self.inner.internals.lock().unwrap();
+                internals.num_conns -= 1;
+                continue;
+            }
+
+            if internals.num_conns < self.inner.config.max_size {
+                internals.num_conns += 1;
+                drop(internals);
+
+                return match self.inner.connect() {
+                    Ok(conn) => {
+                        Ok(PooledConnection {
+                            pool: self.inner.clone(),
+                            conn: Some(conn),
+                        })
+                    }
+                    Err(err) => {
+                        let mut internals = self.inner.internals.lock().unwrap();
+                        internals.num_conns -= 1;
+                        self.inner.cond.notify_one();
+                        Err(PoolError::ConnectError(err))
+                    }
+                };
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PoolError::Timeout);
+            }
+            internals = self.inner.cond.wait_timeout(internals, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Returns information about the current state of the pool.
+    pub fn state(&self) -> State {
+        let internals = self.inner.internals.lock().unwrap();
+        State {
+            connections: internals.num_conns,
+            idle_connections: internals.idle.len() as u32,
+        }
+    }
+
+    /// Returns the pool's configuration.
+    pub fn config(&self) -> &Config {
+        &self.inner.config
+    }
+}
+
+/// A connection checked out from a `Pool`.
+///
+/// The connection is returned to the pool when the `PooledConnection` is
+/// dropped. Connections which have become desynchronized are closed instead.
+/// Note that session state such as `SET` parameters or a custom notice
+/// handler persists across checkouts.
+pub struct PooledConnection {
+    pool: Arc<PoolInner>,
+    conn: Option<IdleConnection>,
+}
+
+impl fmt::Debug for PooledConnection {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        DebugStruct::new(fmt, "PooledConnection")
+            .field("connection", &self.conn.as_ref().unwrap().conn)
+            .finish()
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.put_back(conn);
+        }
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn.as_ref().unwrap().conn
+    }
+}
+
+impl GenericConnection for PooledConnection {
+    fn prepare<'a>(&'a self, query: &str) -> Result<Statement<'a>> {
+        (**self).prepare(query)
+    }
+
+    fn prepare_cached<'a>(&'a self, query: &str) -> Result<Statement<'a>> {
+        (**self).prepare_cached(query)
+    }
+
+    fn execute(&self, query: &str, params: &[&ToSql]) -> Result<u64> {
+        (**self).execute(query, params)
+    }
+
+    fn transaction<'a>(&'a self) -> Result<Transaction<'a>> {
+        (**self).transaction()
+    }
+
+    fn prepare_copy_in<'a>(&'a self, table: &str, columns: &[&str])
+                           -> Result<CopyInStatement<'a>> {
+        (**self).prepare_copy_in(table, columns)
+    }
+
+    fn batch_execute(&self, query: &str) -> Result<()> {
+        (**self).batch_execute(query)
+    }
+
+    fn is_active(&self) -> bool {
+        (**self).is_active()
+    }
+}
@@ -0,0 +1,223 @@
+// SCRAM-SHA-256 client side authentication, as described in RFC 5802 and
+// RFC 7677.
+use hmac::{Hmac, Mac};
+use sha2::{Sha256, Digest};
+use rand::{OsRng, Rng};
+use serialize::base64::{self, ToBase64, FromBase64};
+use std::io;
+use std::mem;
+use std::str;
+
+pub const SCRAM_SHA_256: &'static str = "SCRAM-SHA-256";
+
+const NONCE_LENGTH: usize = 18;
+
+// We don't support channel binding
+const GS2_HEADER: &'static str = "n,,";
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut hmac = Hmac::<Sha256>::new_varkey(key).expect("HMAC accepts keys of any length");
+    hmac.input(data);
+    hmac.result().code().to_vec()
+}
+
+fn hash(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+// The Hi function from RFC 5802, which is PBKDF2 with HMAC-SHA-256
+fn hi(password: &[u8], salt: &[u8], iterations: u32) -> Vec<u8> {
+    let mut first = salt.to_owned();
+    first.extend([0, 0, 0, 1].iter().cloned());
+
+    let mut prev = hmac(password, &first);
+    let mut hi = prev.clone();
+
+    for _ in 1..iterations {
+        prev = hmac(password, &prev);
+        for (hi, prev) in hi.iter_mut().zip(prev.iter()) {
+            *hi ^= *prev;
+        }
+    }
+
+    hi
+}
+
+fn invalid<T>(msg: &'static str) -> io::Result<T> {
+    Err(io::Error::new(io::ErrorKind::InvalidInput, msg))
+}
+
+enum State {
+    Update {
+        nonce: String,
+        password: Vec<u8>,
+        client_first_bare: String,
+    },
+    Finish {
+        salted_password: Vec<u8>,
+        auth_message: String,
+    },
+    Done,
+}
+
+/// The client side of a SCRAM-SHA-256 exchange.
+///
+/// The initial message is available via `message` as soon as the exchange is
+/// created. The server's first message is passed to `update`, after which
+/// `message` returns the client's final message. The server's final message
+/// is passed to `finish`, which verifies the server's signature.
+pub struct ScramSha256 {
+    message: String,
+    state: State,
+}
+
+impl ScramSha256 {
+    /// Begins a new exchange with a nonce from the operating system's secure
+    /// random number generator.
+    ///
+    /// The password is used as-is rather than being normalized with SASLprep,
+    /// which matches the server's behavior for non-ASCII passwords it cannot
+    /// normalize.
+    pub fn new(password: &[u8]) -> io::Result<ScramSha256> {
+        let mut rng = try!(OsRng::new());
+        let mut nonce = [0; NONCE_LENGTH];
+        rng.fill_bytes(&mut nonce);
+        Ok(ScramSha256::with_nonce(password, nonce.to_base64(base64::STANDARD)))
+    }
+
+    fn with_nonce(password: &[u8], nonce: String) -> ScramSha256 {
+        // The server ignores the username here in favor of the one in the
+        // startup message.
+        let client_first_bare = format!("n=,r={}", nonce);
+        ScramSha256 {
+            message: format!("{}{}", GS2_HEADER, client_first_bare),
+            state: State::Update {
+                nonce: nonce,
+                password: password.to_owned(),
+                client_first_bare: client_first_bare,
+            },
+        }
+    }
+
+    /// Returns the message which should be sent to the server.
+    pub fn message(&self) -> &[u8] {
+        self.message.as_bytes()
+    }
+
+    /// Updates the state machine with the contents of a `SASLContinue`
+    /// message.
+    pub fn update(&mut self, message: &[u8]) -> io::Result<()> {
+        let (client_nonce, password, client_first_bare) =
+                match mem::replace(&mut self.state, State::Done) {
+            State::Update { nonce, password, client_first_bare } => {
+                (nonce, password, client_first_bare)
+            }
+            _ => return invalid("invalid SCRAM state"),
+        };
+
+        let message = match str::from_utf8(message) {
+            Ok(message) => message,
+            Err(_) => return invalid("invalid SCRAM server first message"),
+        };
+
+        let mut nonce = None;
+        let mut salt = None;
+        let mut iterations = None;
+        for part in message.split(',') {
+            if part.starts_with("r=") {
+                nonce = Some(&part[2..]);
+            } else if part.starts_with("s=") {
+                salt = Some(&part[2..]);
+            } else if part.starts_with("i=") {
+                iterations = part[2..].parse::<u32>().ok();
+            } else if part.starts_with("m=") {
+                return invalid("unsupported SCRAM extension");
+            }
+        }
+
+        let nonce = match nonce {
+            // The server must append its own nonce to ours
+            Some(nonce) if nonce.starts_with(&client_nonce) && nonce.len() > client_nonce.len() => {
+                nonce
+            }
+            Some(_) => return invalid("invalid SCRAM nonce"),
+            None => return invalid("missing SCRAM nonce"),
+        };
+        let salt = match salt.map(|s| s.from_base64()) {
+            Some(Ok(salt)) => salt,
+            _ => return invalid("invalid SCRAM salt"),
+        };
+        let iterations = match iterations {
+            Some(iterations) if iterations > 0 => iterations,
+            _ => return invalid("invalid SCRAM iteration count"),
+        };
+
+        let salted_password = hi(&password, &salt, iterations);
+        let client_key = hmac(&salted_password, b"Client Key");
+        let stored_key = hash(&client_key);
+
+        let client_final_without_proof = format!("c={},r={}",
+                                                 GS2_HEADER.as_bytes().to_base64(base64::STANDARD),
+                                                 nonce);
+        let auth_message = format!("{},{},{}",
+                                   client_first_bare,
+                                   message,
+                                   client_final_without_proof);
+
+        let client_signature = hmac(&stored_key, auth_message.as_bytes());
+        let proof = client_key.iter()
+                              .zip(client_signature.iter())
+                              .map(|(k, s)| k ^ s)
+                              .collect::<Vec<u8>>();
+
+        self.message = format!("{},p={}",
+                               client_final_without_proof,
+                               proof.to_base64(base64::STANDARD));
+        self.state = State::Finish {
+            salted_password: salted_password,
+            auth_message: auth_message,
+        };
+        Ok(())
+    }
+
+    /// Finalizes the exchange with the contents of a `SASLFinal` message,
+    /// verifying the server's signature.
+    pub fn finish(&mut self, message: &[u8]) -> io::Result<()> {
+        let (salted_password, auth_message) = match mem::replace(&mut self.state, State::Done) {
+            State::Finish { salted_password, auth_message } => (salted_password, auth_message),
+            _ => return invalid("invalid SCRAM state"),
+        };
+
+        let message = match str::from_utf8(message) {
+            Ok(message) => message,
+            Err(_) => return invalid("invalid SCRAM server final message"),
+        };
+
+        let verifier = match message.split(',').next() {
+            Some(part) if part.starts_with("v=") => &part[2..],
+            Some(part) if part.starts_with("e=") => {
+                return Err(io::Error::new(io::ErrorKind::Other,
+                                          format!("SCRAM error: {}", &part[2..])));
+            }
+            _ => return invalid("invalid SCRAM server final message"),
+        };
+        let verifier = match verifier.from_base64() {
+            Ok(verifier) => verifier,
+            Err(_) => return invalid("invalid SCRAM server signature"),
+        };
+
+        let server_key = hmac(&salted_password, b"Server Key");
+        let server_signature = hmac(&server_key, auth_message.as_bytes());
+
+        // compare in constant time so as not to leak how much of the
+        // signature matched
+        let diff = verifier.iter()
+                           .zip(server_signature.iter())
+                           .fold(0, |acc, (a, b)| acc | (a ^ b));
+        if verifier.len() != server_signature.len() || diff != 0 {
+            return invalid("SCRAM server signature mismatch");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! Types and traits for SSL adaptors.
+//!
+//! SSL support is provided by implementations of `NegotiateSsl`, which are
+//! passed to `Connection::connect` via `SslMode`. An implementation backed by
+//! OpenSSL's `SslContext` is available with the `with-openssl` feature, which
+//! is enabled by default.
+pub use io_util::Stream;
+
+use std::error::Error;
+use std::fmt;
+use std::io::prelude::*;
+
+#[cfg(feature = "with-openssl")]
+use openssl::ssl::{Ssl, SslContext, SslStream, SSL_VERIFY_PEER};
+#[cfg(feature = "with-openssl")]
+use openssl_verify::verify_callback;
+
+/// A trait implemented by SSL adaptors.
+pub trait StreamWrapper: fmt::Debug + Read + Write + Send {
+    /// Returns a reference to the underlying `Stream`.
+    fn get_ref(&self) -> &Stream;
+
+    /// Returns a mutable reference to the underlying `Stream`.
+    fn get_mut(&mut self) -> &mut Stream;
+
+    /// Returns the number of bytes which have been read from the underlying
+    /// `Stream` and decrypted, but not yet returned by `read`.
+    ///
+    /// A `Connection` waiting for notifications only polls the underlying
+    /// `Stream` when this is zero. The default implementation returns 0.
+    fn pending(&self) -> usize {
+        0
+    }
+}
+
+impl StreamWrapper for Stream {
+    fn get_ref(&self) -> &Stream {
+        self
+    }
+
+    fn get_mut(&mut self) -> &mut Stream {
+        self
+    }
+}
+
+/// The verification of the server's certificate requested by an `SslMode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// The certificate is verified as the implementation is configured to.
+    Default,
+    /// The certificate must be signed by a trusted certificate authority.
+    Ca,
+    /// The certificate must be signed by a trusted certificate authority and
+    /// must match the host name of the server.
+    Full,
+}
+
+/// A trait implemented by types that can initiate an SSL session over a
+/// stream.
+pub trait NegotiateSsl: fmt::Debug + Send + Sync {
+    /// Negotiates an SSL session, returning a wrapper around the provided
+    /// stream.
+    ///
+    /// The host portion of the connection parameters is provided for hostname
+    /// verification. Unix socket connections are given `localhost`.
+    fn negotiate_ssl(&self,
+                     host: &str,
+                     verification: Verification,
+                     stream: Stream)
+                     -> Result<Box<StreamWrapper>, Box<Error + Sync + Send>>;
+}
+
+#[cfg(feature = "with-openssl")]
+impl StreamWrapper for SslStream<Stream> {
+    fn get_ref(&self) -> &Stream {
+        self.get_ref()
+    }
+
+    fn get_mut(&mut self) -> &mut Stream {
+        self.get_mut()
+    }
+
+    fn pending(&self) -> usize {
+        self.ssl().pending()
+    }
+}
+
+#[cfg(feature = "with-openssl")]
+impl NegotiateSsl for SslContext {
+    fn negotiate_ssl(&self,
+                     host: &str,
+                     verification: Verification,
+                     stream: Stream)
+                     -> Result<Box<StreamWrapper>, Box<Error + Sync + Send>> {
+        let mut ssl = try!(Ssl::new(self));
+        match verification {
+            Verification::Default => {}
+            Verification::Ca => ssl.set_verify(SSL_VERIFY_PEER, None),
+            Verification::Full => {
+                try!(ssl.set_hostname(host));
+                let host = host.to_owned();
+                ssl.set_verify_callback(SSL_VERIFY_PEER,
+                                        move |preverify_ok, x509_ctx| {
+                                            verify_callback(&host, preverify_ok, x509_ctx)
+                                        });
+            }
+        }
+
+        let stream = try!(SslStream::connect(ssl, stream));
+        Ok(Box::new(stream))
+    }
+}
@@ -0,0 +1,334 @@
+use std::io;
+use std::io::prelude::*;
+use std::slice;
+use std::vec;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, Kind, FromSql, ToSql, IsNull};
+
+/// Information about a dimension of an `Array`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Dimension {
+    /// The number of elements in the dimension.
+    pub len: usize,
+    /// The index of the first element of the dimension.
+    pub lower_bound: i32,
+}
+
+// The number of elements described by a set of dimensions, or `None` if it
+// overflows.
+fn element_count(dims: &[Dimension]) -> Option<usize> {
+    if dims.is_empty() {
+        return Some(0);
+    }
+    dims.iter().fold(Some(1), |acc, dim| acc.and_then(|acc| acc.checked_mul(dim.len)))
+}
+
+/// A multi-dimensional Postgres array.
+///
+/// Postgres arrays may have any number of dimensions, each with its own
+/// lower bound. An array with no dimensions is empty. Elements are stored in
+/// row-major order, as they are by Postgres.
+///
+/// Arrays containing `NULL` elements can be read as an `Array<Option<T>>`,
+/// and `None` elements are written as `NULL`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # fn foo() -> postgres::Result<()> {
+/// # use postgres::{Connection, SslMode};
+/// use postgres::types::Array;
+///
+/// # let conn = Connection::connect("", &SslMode::None).unwrap();
+/// let stmt = try!(conn.prepare("SELECT '{{1,2,3},{4,5,6}}'::INT4[]"));
+/// let rows = try!(stmt.query(&[]));
+/// let array: Array<i32> = rows.iter().next().unwrap().get(0);
+/// assert_eq!(Some(&6), array.get(&[2, 3]));
+///
+/// let matrix: Array<i32> = Array::from(vec![vec![1, 2], vec![3, 4]]);
+/// try!(conn.execute("SELECT transform($1)", &[&matrix]));
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Array<T> {
+    dims: Vec<Dimension>,
+    data: Vec<T>,
+}
+
+impl<T> Array<T> {
+    /// Creates a new array from its elements, in row-major order, and
+    /// dimensions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of elements doesn't match the dimensions.
+    pub fn from_parts(data: Vec<T>, dimensions: Vec<Dimension>) -> Array<T> {
+        assert!(element_count(&dimensions) == Some(data.len()),
+                "size mismatch: {} elements for dimensions {:?}",
+                data.len(),
+                dimensions);
+
+        Array {
+            dims: dimensions,
+            data: data,
+        }
+    }
+
+    /// Creates a new one-dimensional array from a `Vec`.
+    pub fn from_vec(data: Vec<T>, lower_bound: i32) -> Array<T> {
+        let dims = if data.is_empty() {
+            vec![]
+        } else {
+            vec![Dimension {
+                     len: data.len(),
+                     lower_bound: lower_bound,
+                 }]
+        };
+
+        Array {
+            dims: dims,
+            data: data,
+        }
+    }
+
+    /// Creates a new array from arrays of identical dimensions, which become
+    /// the slices of a new outermost dimension with a lower bound of 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arrays have different dimensions.
+    pub fn from_arrays(arrays: Vec<Array<T>>) -> Array<T> {
+        let mut dims = match arrays.first() {
+            Some(first) => first.dims.clone(),
+            None => return Array::from_parts(vec![], vec![]),
+        };
+
+        let mut data = vec![];
+        let len = arrays.len();
+        for array in arrays {
+            assert!(array.dims == dims,
+                    "dimension mismatch: {:?} != {:?}",
+                    array.dims,
+                    dims);
+            data.extend(array.data);
+        }
+
+        if dims.is_empty() {
+            return Array::from_parts(data, dims);
+        }
+
+        dims.insert(0,
+                    Dimension {
+                        len: len,
+                        lower_bound: 1,
+                    });
+        Array::from_parts(data, dims)
+    }
+
+    /// Returns the dimensions of the array.
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.dims
+    }
+
+    /// Returns the element at the specified indices, one per dimension, or
+    /// `None` if they are out of bounds.
+    ///
+    /// As in Postgres, indices are relative to each dimension's lower bound,
+    /// which is usually 1.
+    pub fn get(&self, indices: &[i32]) -> Option<&T> {
+        if indices.len() != self.dims.len() {
+            return None;
+        }
+
+        let mut idx = 0;
+        for (&index, dim) in indices.iter().zip(self.dims.iter()) {
+            let offset = index as i64 - dim.lower_bound as i64;
+            if offset < 0 || offset >= dim.len as i64 {
+                return None;
+            }
+            idx = idx * dim.len + offset as usize;
+        }
+
+        self.data.get(idx)
+    }
+
+    /// Returns an iterator over the elements of the array in row-major order.
+    pub fn iter(&self) -> slice::Iter<T> {
+        self.data.iter()
+    }
+
+    /// Consumes the array, returning its elements in row-major order.
+    pub fn into_inner(self) -> Vec<T> {
+        self.data
+    }
+}
+
+/// Creates a one-dimensional array with a lower bound of 1.
+impl<T> From<Vec<T>> for Array<T> {
+    fn from(data: Vec<T>) -> Array<T> {
+        Array::from_vec(data, 1)
+    }
+}
+
+/// Creates a two-dimensional array with lower bounds of 1.
+///
+/// # Panics
+///
+/// Panics if the inner `Vec`s have different lengths.
+impl<T> From<Vec<Vec<T>>> for Array<T> {
+    fn from(data: Vec<Vec<T>>) -> Array<T> {
+        Array::from_arrays(data.into_iter().map(Array::from).collect())
+    }
+}
+
+impl<T> IntoIterator for Array<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Array<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.data.iter()
+    }
+}
+
+impl<T: FromSql> FromSql for Array<T> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Array<T>> {
+        let element_type = match ty.kind() {
+            &Kind::Array(ref element_type) => element_type,
+            _ => return Err(Error::WrongType(ty.clone())),
+        };
+
+        let ndim = try!(raw.read_i32::<BigEndian>());
+        if ndim < 0 {
+            return Err(Error::BadResponse);
+        }
+        let _has_nulls = try!(raw.read_i32::<BigEndian>());
+        let _element_oid = try!(raw.read_u32::<BigEndian>());
+
+        // The counts are untrusted, so nothing is preallocated
+        let mut dims = vec![];
+        for _ in 0..ndim {
+            let len = try!(raw.read_i32::<BigEndian>());
+            if len < 0 {
+                return Err(Error::BadResponse);
+            }
+            dims.push(Dimension {
+                len: len as usize,
+                lower_bound: try!(raw.read_i32::<BigEndian>()),
+            });
+        }
+
+        let count = try!(element_count(&dims).ok_or(Error::BadResponse));
+
+        let mut data = vec![];
+        for _ in 0..count {
+            let len = try!(raw.read_i32::<BigEndian>());
+            let element = if len < 0 {
+                try!(T::from_sql_nullable(element_type, None::<&mut R>))
+            } else {
+                let mut limit = (&mut *raw).take(len as u64);
+                let element = try!(T::from_sql_nullable(element_type, Some(&mut limit)));
+                if limit.limit() != 0 {
+                    return Err(Error::BadResponse);
+                }
+                element
+            };
+            data.push(element);
+        }
+
+        Ok(Array {
+            dims: dims,
+            data: data,
+        })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match ty.kind() {
+            &Kind::Array(ref element_type) => <T as FromSql>::accepts(element_type),
+            _ => false,
+        }
+    }
+}
+
+/// Only one-dimensional arrays can be read as a `Vec`; use `Array` for arrays
+/// with more dimensions. Lower bounds are ignored.
+impl<T: FromSql> FromSql for Vec<T> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Vec<T>> {
+        let array: Array<T> = try!(FromSql::from_sql(ty, raw));
+        if array.dimensions().len() > 1 {
+            let err = io::Error::new(io::ErrorKind::InvalidInput,
+                                     "multi-dimensional arrays cannot be read as a Vec");
+            return Err(Error::IoError(err));
+        }
+        Ok(array.into_inner())
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <Array<T> as FromSql>::accepts(ty)
+    }
+}
+
+// Writes an array in the binary format, setting the has-nulls flag if any
+// element is `NULL`.
+pub fn write_array<T, W>(member_type: &Type, dims: &[Dimension], data: &[T], mut w: &mut W)
+                         -> Result<()>
+        where T: ToSql, W: Write + ?Sized {
+    let mut elements = vec![];
+    let mut has_nulls = false;
+    let mut inner_buf = vec![];
+    for e in data {
+        match try!(e.to_sql(member_type, &mut inner_buf)) {
+            IsNull::No => {
+                try!(elements.write_i32::<BigEndian>(inner_buf.len() as i32));
+                try!(elements.write_all(&inner_buf));
+            }
+            IsNull::Yes => {
+                has_nulls = true;
+                try!(elements.write_i32::<BigEndian>(-1));
+            }
+        }
+        inner_buf.clear();
+    }
+
+    try!(w.write_i32::<BigEndian>(dims.len() as i32));
+    try!(w.write_i32::<BigEndian>(has_nulls as i32));
+    try!(w.write_u32::<BigEndian>(member_type.oid()));
+    for dim in dims {
+        try!(w.write_i32::<BigEndian>(dim.len as i32));
+        try!(w.write_i32::<BigEndian>(dim.lower_bound));
+    }
+    try!(w.write_all(&elements));
+
+    Ok(())
+}
+
+impl<T: ToSql> ToSql for Array<T> {
+    to_sql_checked!();
+
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, w: &mut W) -> Result<IsNull> {
+        let member_type = match ty.kind() {
+            &Kind::Array(ref member) => member,
+            _ => panic!("expected array type"),
+        };
+
+        try!(write_array(member_type, &self.dims, &self.data, w));
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match ty.kind() {
+            &Kind::Array(ref member) => <T as ToSql>::accepts(member),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::io::prelude::*;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, FromSql, ToSql, IsNull};
+use util;
+
+/// A vector of bits with an exact length, corresponding to the Postgres `BIT`
+/// and `VARBIT` types.
+///
+/// Bits are numbered from the left, as Postgres displays them, so the first
+/// bit is the most significant bit of the first byte.
+///
+/// # Examples
+///
+/// ```rust
+/// use postgres::types::BitVec;
+///
+/// let flags: BitVec = vec![true, false, true].into_iter().collect();
+/// assert_eq!(3, flags.len());
+/// assert_eq!(Some(false), flags.get(1));
+/// assert_eq!("101", flags.to_string());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    len: usize,
+    // Unused bits in the last byte are always zero
+    bytes: Vec<u8>,
+}
+
+fn byte_len(len: usize) -> usize {
+    (len + 7) / 8
+}
+
+fn mask(idx: usize) -> u8 {
+    0x80 >> (idx % 8)
+}
+
+impl BitVec {
+    /// Creates a new, empty bit vector.
+    pub fn new() -> BitVec {
+        BitVec::default()
+    }
+
+    /// Creates a bit vector of `len` bits, all set to `bit`.
+    pub fn from_elem(len: usize, bit: bool) -> BitVec {
+        let mut vec = BitVec {
+            len: len,
+            bytes: vec![if bit { 0xff } else { 0 }; byte_len(len)],
+        };
+        vec.clear_padding();
+        vec
+    }
+
+    /// Creates a bit vector from bytes, with eight bits per byte.
+    pub fn from_bytes(bytes: &[u8]) -> BitVec {
+        BitVec {
+            len: bytes.len() * 8,
+            bytes: bytes.to_owned(),
+        }
+    }
+
+    fn clear_padding(&mut self) {
+        if self.len % 8 != 0 {
+            if let Some(last) = self.bytes.last_mut() {
+                *last &= !(0xff >> (self.len % 8));
+            }
+        }
+    }
+
+    /// Returns the number of bits in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determines if the vector contains no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit at the specified index, or `None` if it is out of
+    /// bounds.
+    pub fn get(&self, idx: usize) -> Option<bool> {
+        if idx >= self.len {
+            return None;
+        }
+        Some(self.bytes[idx / 8] & mask(idx) != 0)
+    }
+
+    /// Sets the bit at the specified index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, idx: usize, bit: bool) {
+        assert!(idx < self.len, "index {} out of bounds for length {}", idx, self.len);
+        if bit {
+            self.bytes[idx / 8] |= mask(idx);
+        } else {
+            self.bytes[idx / 8] &= !mask(idx);
+        }
+    }
+
+    /// Appends a bit to the end of the vector.
+    pub fn push(&mut self, bit: bool) {
+        if self.len % 8 == 0 {
+            self.bytes.push(0);
+        }
+        self.len += 1;
+        let idx = self.len - 1;
+        self.set(idx, bit);
+    }
+
+    /// Returns an iterator over the bits of the vector.
+    pub fn iter(&self) -> BitIter {
+        BitIter {
+            vec: self,
+            idx: 0,
+        }
+    }
+
+    /// Returns the bytes of the vector, with any unused bits of the last byte
+    /// set to zero.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> BitVec {
+        let mut vec = BitVec::new();
+        for bit in iter {
+            vec.push(bit);
+        }
+        vec
+    }
+}
+
+impl<'a> IntoIterator for &'a BitVec {
+    type Item = bool;
+    type IntoIter = BitIter<'a>;
+
+    fn into_iter(self) -> BitIter<'a> {
+        self.iter()
+    }
+}
+
+/// Formats the bits as Postgres does, like `10110`.
+impl fmt::Display for BitVec {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for bit in self {
+            try!(fmt.write_str(if bit { "1" } else { "0" }));
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the bits of a `BitVec`.
+#[derive(Clone, Debug)]
+pub struct BitIter<'a> {
+    vec: &'a BitVec,
+    idx: usize,
+}
+
+impl<'a> Iterator for BitIter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        let bit = self.vec.get(self.idx);
+        if bit.is_some() {
+            self.idx += 1;
+        }
+        bit
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.idx;
+        (len, Some(len))
+    }
+}
+
+impl FromSql for BitVec {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<BitVec> {
+        let len = try!(raw.read_i32::<BigEndian>());
+        if len < 0 {
+            return Err(Error::BadResponse);
+        }
+
+        let mut vec = BitVec {
+            len: len as usize,
+            bytes: vec![0; byte_len(len as usize)],
+        };
+        try!(util::read_all(raw, &mut vec.bytes));
+        vec.clear_padding();
+        Ok(vec)
+    }
+
+    accepts!(Type::Bit, Type::Varbit);
+}
+
+impl ToSql for BitVec {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_i32::<BigEndian>(self.len as i32));
+        try!(w.write_all(&self.bytes));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Bit, Type::Varbit);
+    to_sql_checked!();
+}
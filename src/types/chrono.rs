@@ -0,0 +1,137 @@
+extern crate chrono;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+use self::chrono::{Duration, NaiveDate, NaiveTime, NaiveDateTime, DateTime, UTC, FixedOffset};
+use std::io;
+use std::io::prelude::*;
+use std::{i32, i64};
+
+use {Result, Error};
+use types::{Type, FromSql, ToSql, IsNull};
+use types::infinite::infinite_error;
+
+fn base() -> NaiveDateTime {
+    NaiveDate::from_ymd(2000, 1, 1).and_hms(0, 0, 0)
+}
+
+fn out_of_range() -> Error {
+    let err = io::Error::new(io::ErrorKind::InvalidInput, "value out of range");
+    Error::IoError(err)
+}
+
+impl FromSql for NaiveDateTime {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<NaiveDateTime> {
+        let t = try!(raw.read_i64::<BigEndian>());
+        if t == i64::MAX || t == i64::MIN {
+            return Err(infinite_error());
+        }
+
+        base().checked_add(Duration::microseconds(t)).ok_or_else(out_of_range)
+    }
+
+    accepts!(Type::Timestamp);
+}
+
+impl ToSql for NaiveDateTime {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        let t = match (*self - base()).num_microseconds() {
+            Some(t) => t,
+            None => return Err(out_of_range()),
+        };
+        try!(w.write_i64::<BigEndian>(t));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Timestamp);
+    to_sql_checked!();
+}
+
+impl FromSql for DateTime<UTC> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<DateTime<UTC>> {
+        let naive = try!(NaiveDateTime::from_sql(ty, raw));
+        Ok(DateTime::from_utc(naive, UTC))
+    }
+
+    accepts!(Type::TimestampTZ);
+}
+
+impl ToSql for DateTime<UTC> {
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, w: &mut W) -> Result<IsNull> {
+        self.naive_utc().to_sql(ty, w)
+    }
+
+    accepts!(Type::TimestampTZ);
+    to_sql_checked!();
+}
+
+/// Postgres doesn't store the time zone of a `TIMESTAMP WITH TIME ZONE`, so
+/// values are read with a UTC offset of zero.
+impl FromSql for DateTime<FixedOffset> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<DateTime<FixedOffset>> {
+        let utc = try!(DateTime::<UTC>::from_sql(ty, raw));
+        Ok(utc.with_timezone(&FixedOffset::east(0)))
+    }
+
+    accepts!(Type::TimestampTZ);
+}
+
+impl ToSql for DateTime<FixedOffset> {
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, w: &mut W) -> Result<IsNull> {
+        self.naive_utc().to_sql(ty, w)
+    }
+
+    accepts!(Type::TimestampTZ);
+    to_sql_checked!();
+}
+
+impl FromSql for NaiveDate {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<NaiveDate> {
+        let days = try!(raw.read_i32::<BigEndian>());
+        if days == i32::MAX || days == i32::MIN {
+            return Err(infinite_error());
+        }
+
+        base().date().checked_add(Duration::days(days as i64)).ok_or_else(out_of_range)
+    }
+
+    accepts!(Type::Date);
+}
+
+impl ToSql for NaiveDate {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        let days = (*self - base().date()).num_days();
+        if days <= i32::MIN as i64 || days >= i32::MAX as i64 {
+            return Err(out_of_range());
+        }
+        try!(w.write_i32::<BigEndian>(days as i32));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Date);
+    to_sql_checked!();
+}
+
+/// `NaiveTime` can't represent `24:00:00`, so reading that value fails.
+impl FromSql for NaiveTime {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<NaiveTime> {
+        let usec = try!(raw.read_i64::<BigEndian>());
+        if usec < 0 || usec >= Duration::days(1).num_microseconds().unwrap() {
+            return Err(out_of_range());
+        }
+
+        Ok(NaiveTime::from_hms(0, 0, 0) + Duration::microseconds(usec))
+    }
+
+    accepts!(Type::Time);
+}
+
+impl ToSql for NaiveTime {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        let usec = *self - NaiveTime::from_hms(0, 0, 0);
+        try!(w.write_i64::<BigEndian>(usec.num_microseconds().unwrap()));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Time);
+    to_sql_checked!();
+}
@@ -0,0 +1,279 @@
+use std::io::prelude::*;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, FromSql, ToSql, IsNull};
+
+/// A point in two dimensions, corresponding to the Postgres `POINT` type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Point {
+    /// The x coordinate.
+    pub x: f64,
+    /// The y coordinate.
+    pub y: f64,
+}
+
+impl Point {
+    /// Creates a point from its coordinates.
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x: x, y: y }
+    }
+}
+
+fn read_point<R: Read>(raw: &mut R) -> Result<Point> {
+    let x = try!(raw.read_f64::<BigEndian>());
+    let y = try!(raw.read_f64::<BigEndian>());
+    Ok(Point::new(x, y))
+}
+
+fn write_point<W: Write+?Sized>(point: &Point, mut w: &mut W) -> Result<()> {
+    try!(w.write_f64::<BigEndian>(point.x));
+    try!(w.write_f64::<BigEndian>(point.y));
+    Ok(())
+}
+
+fn read_points<R: Read>(raw: &mut R) -> Result<Vec<Point>> {
+    let count = try!(raw.read_i32::<BigEndian>());
+    if count < 0 {
+        return Err(Error::BadResponse);
+    }
+
+    // The count is untrusted, so nothing is preallocated
+    let mut points = vec![];
+    for _ in 0..count {
+        points.push(try!(read_point(&mut *raw)));
+    }
+    Ok(points)
+}
+
+fn write_points<W: Write+?Sized>(points: &[Point], mut w: &mut W) -> Result<()> {
+    try!(w.write_i32::<BigEndian>(points.len() as i32));
+    for point in points {
+        try!(write_point(point, &mut *w));
+    }
+    Ok(())
+}
+
+impl FromSql for Point {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Point> {
+        read_point(raw)
+    }
+
+    accepts!(Type::Point);
+}
+
+impl ToSql for Point {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, w: &mut W) -> Result<IsNull> {
+        try!(write_point(self, w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Point);
+    to_sql_checked!();
+}
+
+/// A finite line segment, corresponding to the Postgres `LSEG` type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineSegment {
+    /// The first end of the segment.
+    pub start: Point,
+    /// The second end of the segment.
+    pub end: Point,
+}
+
+impl FromSql for LineSegment {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<LineSegment> {
+        let start = try!(read_point(&mut *raw));
+        let end = try!(read_point(&mut *raw));
+        Ok(LineSegment {
+            start: start,
+            end: end,
+        })
+    }
+
+    accepts!(Type::Lseg);
+}
+
+impl ToSql for LineSegment {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, w: &mut W) -> Result<IsNull> {
+        try!(write_point(&self.start, &mut *w));
+        try!(write_point(&self.end, &mut *w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Lseg);
+    to_sql_checked!();
+}
+
+/// A rectangle with sides parallel to the axes, corresponding to the Postgres
+/// `BOX` type.
+///
+/// Postgres swaps the coordinates of boxes as needed so that `high` is the
+/// upper right corner and `low` is the lower left corner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// The upper right corner.
+    pub high: Point,
+    /// The lower left corner.
+    pub low: Point,
+}
+
+impl FromSql for BoundingBox {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<BoundingBox> {
+        let high = try!(read_point(&mut *raw));
+        let low = try!(read_point(&mut *raw));
+        Ok(BoundingBox {
+            high: high,
+            low: low,
+        })
+    }
+
+    accepts!(Type::Box);
+}
+
+impl ToSql for BoundingBox {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, w: &mut W) -> Result<IsNull> {
+        try!(write_point(&self.high, &mut *w));
+        try!(write_point(&self.low, &mut *w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Box);
+    to_sql_checked!();
+}
+
+/// A sequence of connected points, corresponding to the Postgres `PATH`
+/// type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    /// The points of the path.
+    pub points: Vec<Point>,
+    /// Whether the last point connects back to the first.
+    pub closed: bool,
+}
+
+impl FromSql for Path {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Path> {
+        let closed = match try!(raw.read_u8()) {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::BadResponse),
+        };
+        let points = try!(read_points(raw));
+        Ok(Path {
+            points: points,
+            closed: closed,
+        })
+    }
+
+    accepts!(Type::Path);
+}
+
+impl ToSql for Path {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_u8(self.closed as u8));
+        try!(write_points(&self.points, w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Path);
+    to_sql_checked!();
+}
+
+/// A closed polygon, corresponding to the Postgres `POLYGON` type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+    /// The vertices of the polygon.
+    pub points: Vec<Point>,
+}
+
+impl FromSql for Polygon {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Polygon> {
+        Ok(Polygon { points: try!(read_points(raw)) })
+    }
+
+    accepts!(Type::Polygon);
+}
+
+impl ToSql for Polygon {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, w: &mut W) -> Result<IsNull> {
+        try!(write_points(&self.points, w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Polygon);
+    to_sql_checked!();
+}
+
+/// An infinite line, corresponding to the Postgres `LINE` type.
+///
+/// The line consists of the points satisfying `a * x + b * y + c = 0`, where
+/// `a` and `b` are not both zero.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Line {
+    /// The coefficient of x.
+    pub a: f64,
+    /// The coefficient of y.
+    pub b: f64,
+    /// The constant term.
+    pub c: f64,
+}
+
+impl FromSql for Line {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Line> {
+        let a = try!(raw.read_f64::<BigEndian>());
+        let b = try!(raw.read_f64::<BigEndian>());
+        let c = try!(raw.read_f64::<BigEndian>());
+        Ok(Line { a: a, b: b, c: c })
+    }
+
+    accepts!(Type::Line);
+}
+
+impl ToSql for Line {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_f64::<BigEndian>(self.a));
+        try!(w.write_f64::<BigEndian>(self.b));
+        try!(w.write_f64::<BigEndian>(self.c));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Line);
+    to_sql_checked!();
+}
+
+/// A circle, corresponding to the Postgres `CIRCLE` type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Circle {
+    /// The center of the circle.
+    pub center: Point,
+    /// The radius of the circle.
+    pub radius: f64,
+}
+
+impl FromSql for Circle {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Circle> {
+        let center = try!(read_point(&mut *raw));
+        let radius = try!(raw.read_f64::<BigEndian>());
+        if radius < 0. {
+            return Err(Error::BadResponse);
+        }
+        Ok(Circle {
+            center: center,
+            radius: radius,
+        })
+    }
+
+    accepts!(Type::Circle);
+}
+
+impl ToSql for Circle {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(write_point(&self.center, &mut *w));
+        try!(w.write_f64::<BigEndian>(self.radius));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Circle);
+    to_sql_checked!();
+}
@@ -0,0 +1,89 @@
+use std::io;
+use std::io::prelude::*;
+use std::{i32, i64};
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, FromSql, ToSql, IsNull};
+
+/// A wrapper adding Postgres's `infinity` and `-infinity` values to a date or
+/// timestamp type.
+///
+/// `Infinite<T>` can be used with any type `T` which maps to `DATE`,
+/// `TIMESTAMP` or `TIMESTAMP WITH TIME ZONE`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Infinite<T> {
+    /// `-infinity`, which is earlier than all other values.
+    NegInfinity,
+    /// A finite value.
+    Value(T),
+    /// `infinity`, which is later than all other values.
+    PosInfinity,
+}
+
+// The error returned when an infinite value is read as a finite type
+pub fn infinite_error() -> Error {
+    let err = io::Error::new(io::ErrorKind::InvalidInput,
+                             "infinite values must be read as an `Infinite`");
+    Error::IoError(err)
+}
+
+impl<T: FromSql> FromSql for Infinite<T> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Infinite<T>> {
+        let mut buf = vec![];
+        try!(raw.read_to_end(&mut buf));
+
+        match *ty {
+            Type::Date => {
+                match try!((&buf[..]).read_i32::<BigEndian>()) {
+                    i32::MIN => return Ok(Infinite::NegInfinity),
+                    i32::MAX => return Ok(Infinite::PosInfinity),
+                    _ => {}
+                }
+            }
+            _ => {
+                match try!((&buf[..]).read_i64::<BigEndian>()) {
+                    i64::MIN => return Ok(Infinite::NegInfinity),
+                    i64::MAX => return Ok(Infinite::PosInfinity),
+                    _ => {}
+                }
+            }
+        }
+
+        T::from_sql(ty, &mut &buf[..]).map(Infinite::Value)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty {
+            Type::Date | Type::Timestamp | Type::TimestampTZ => <T as FromSql>::accepts(ty),
+            _ => false,
+        }
+    }
+}
+
+impl<T: ToSql> ToSql for Infinite<T> {
+    to_sql_checked!();
+
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, mut w: &mut W) -> Result<IsNull> {
+        let negative = match *self {
+            Infinite::Value(ref value) => return value.to_sql(ty, w),
+            Infinite::NegInfinity => true,
+            Infinite::PosInfinity => false,
+        };
+
+        match *ty {
+            Type::Date => {
+                try!(w.write_i32::<BigEndian>(if negative { i32::MIN } else { i32::MAX }))
+            }
+            _ => try!(w.write_i64::<BigEndian>(if negative { i64::MIN } else { i64::MAX })),
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty {
+            Type::Date | Type::Timestamp | Type::TimestampTZ => <T as ToSql>::accepts(ty),
+            _ => false,
+        }
+    }
+}
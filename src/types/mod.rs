@@ -1,5 +1,16 @@
 //! Traits dealing with Postgres data types
+pub use self::array::{Array, Dimension};
+pub use self::bit::{BitVec, BitIter};
+pub use self::geo::{Point, LineSegment, BoundingBox, Path, Polygon, Line, Circle};
+pub use self::infinite::Infinite;
+pub use self::net::{IpNetwork, MacAddress};
+pub use self::numeric::{Numeric, ParseNumericError};
+pub use self::range::{Range, RangeBound, BoundType};
 pub use self::slice::Slice;
+#[cfg(feature = "serde")]
+pub use self::serde::Json;
+#[cfg(feature = "time")]
+pub use self::time::{Date, Time, TimeTz, Interval};
 
 use std::collections::HashMap;
 use std::fmt;
@@ -42,10 +53,83 @@ macro_rules! to_sql_checked {
     }
 }
 
+/// Generates `FromSql` and `ToSql` implementations mapping a Rust enum to a
+/// Postgres enum.
+///
+/// Each variant is paired with the label of the Postgres enum value it
+/// corresponds to. The implementations only accept Postgres enums whose labels
+/// exactly match the ones listed, so a mismatch is reported as
+/// `Error::WrongType` rather than when a value is converted.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate postgres;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Mood {
+///     Sad,
+///     Ok,
+///     Happy,
+/// }
+///
+/// pg_enum!(Mood {
+///     Sad => "sad",
+///     Ok => "ok",
+///     Happy => "happy",
+/// });
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! pg_enum {
+    ($name:ident { $($variant:ident => $label:tt),+ $(,)* }) => {
+        impl $crate::types::FromSql for $name {
+            fn from_sql<R: ::std::io::Read>(ty: &$crate::types::Type, raw: &mut R)
+                                            -> $crate::Result<$name> {
+                let label: String = try!($crate::types::FromSql::from_sql(ty, raw));
+                match &*label {
+                    $($label => Ok($name::$variant),)+
+                    _ => Err($crate::Error::BadResponse),
+                }
+            }
+
+            fn accepts(ty: &$crate::types::Type) -> bool {
+                $crate::types::__enum_accepts(ty, &[$($label),+])
+            }
+        }
+
+        impl $crate::types::ToSql for $name {
+            to_sql_checked!();
+
+            fn to_sql<W: ::std::io::Write+?Sized>(&self, ty: &$crate::types::Type, w: &mut W)
+                                                  -> $crate::Result<$crate::types::IsNull> {
+                let label = match *self {
+                    $($name::$variant => $label,)+
+                };
+                $crate::types::ToSql::to_sql(&label, ty, w)
+            }
+
+            fn accepts(ty: &$crate::types::Type) -> bool {
+                $crate::types::__enum_accepts(ty, &[$($label),+])
+            }
+        }
+    }
+}
+
 #[cfg(feature = "uuid")]
 mod uuid;
 #[cfg(feature = "time")]
 mod time;
+#[cfg(feature = "chrono")]
+mod chrono;
+mod array;
+mod bit;
+mod geo;
+mod infinite;
+mod net;
+mod numeric;
+mod range;
 mod slice;
 #[cfg(feature = "rustc-serialize")]
 mod rustc_serialize;
@@ -64,6 +148,8 @@ pub enum Kind {
     Array(Type),
     /// A range type along with the type of its elements.
     Range(Type),
+    /// An enum type along with its labels, in sort order.
+    Enum(Vec<String>),
 }
 
 macro_rules! as_pat {
@@ -523,6 +609,27 @@ impl FromSql for Vec<u8> {
     accepts!(Type::Bytea);
 }
 
+// Enum values are sent as their labels, so they can be read and written as
+// strings
+fn is_enum(kind: &Kind) -> bool {
+    match *kind {
+        Kind::Enum(_) => true,
+        _ => false,
+    }
+}
+
+/// Used by `pg_enum!` to check that a Postgres enum has exactly the expected
+/// labels.
+#[doc(hidden)]
+pub fn __enum_accepts(ty: &Type, labels: &[&str]) -> bool {
+    match *ty.kind() {
+        Kind::Enum(ref variants) => {
+            variants.len() == labels.len() && variants.iter().all(|v| labels.contains(&&**v))
+        }
+        _ => false,
+    }
+}
+
 impl FromSql for String {
     fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<String> {
         let mut buf = vec![];
@@ -534,6 +641,7 @@ impl FromSql for String {
         match *ty {
             Type::Varchar | Type::Text | Type::Bpchar | Type::Name => true,
             Type::Other(ref u) if u.name() == "citext" => true,
+            Type::Other(ref u) => is_enum(u.kind()),
             _ => false,
         }
     }
@@ -714,6 +822,7 @@ impl<'a> ToSql for &'a str {
         match *ty {
             Type::Varchar | Type::Text | Type::Bpchar | Type::Name => true,
             Type::Other(ref u) if u.name() == "citext" => true,
+            Type::Other(ref u) => is_enum(u.kind()),
             _ => false,
         }
     }
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, FromSql, ToSql, IsNull};
+use util;
+
+// Postgres's own address family values, which don't match the platform's
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = 3;
+
+fn max_prefix(addr: &IpAddr) -> u8 {
+    match *addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+/// An IP address with a network prefix length, corresponding to the Postgres
+/// `CIDR` type or an `INET` value with a netmask.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use postgres::types::IpNetwork;
+///
+/// let subnet = IpNetwork::new(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0)), 16).unwrap();
+/// assert_eq!("192.168.0.0/16", subnet.to_string());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// Creates a network from an address and a prefix length, returning
+    /// `None` if the prefix is longer than the address.
+    pub fn new(addr: IpAddr, prefix: u8) -> Option<IpNetwork> {
+        if prefix > max_prefix(&addr) {
+            return None;
+        }
+
+        Some(IpNetwork {
+            addr: addr,
+            prefix: prefix,
+        })
+    }
+
+    /// Returns the address.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the prefix length in bits.
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+fn read_inet<R: Read>(raw: &mut R) -> Result<IpNetwork> {
+    let family = try!(raw.read_u8());
+    let prefix = try!(raw.read_u8());
+    let _is_cidr = try!(raw.read_u8());
+    let len = try!(raw.read_u8());
+
+    let addr = match (family, len) {
+        (PGSQL_AF_INET, 4) => {
+            let mut octets = [0; 4];
+            try!(util::read_all(raw, &mut octets));
+            IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]))
+        }
+        (PGSQL_AF_INET6, 16) => {
+            let mut segments = [0; 8];
+            for segment in &mut segments {
+                *segment = try!(raw.read_u16::<BigEndian>());
+            }
+            IpAddr::V6(Ipv6Addr::new(segments[0], segments[1], segments[2], segments[3],
+                                     segments[4], segments[5], segments[6], segments[7]))
+        }
+        _ => return Err(Error::BadResponse),
+    };
+
+    IpNetwork::new(addr, prefix).ok_or(Error::BadResponse)
+}
+
+fn write_inet<W: Write+?Sized>(ty: &Type, network: &IpNetwork, mut w: &mut W) -> Result<()> {
+    let is_cidr = *ty == Type::Cidr;
+    match network.addr {
+        IpAddr::V4(ref addr) => {
+            try!(w.write_all(&[PGSQL_AF_INET, network.prefix, is_cidr as u8, 4]));
+            try!(w.write_all(&addr.octets()));
+        }
+        IpAddr::V6(ref addr) => {
+            try!(w.write_all(&[PGSQL_AF_INET6, network.prefix, is_cidr as u8, 16]));
+            for &segment in &addr.segments() {
+                try!(w.write_u16::<BigEndian>(segment));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl FromSql for IpNetwork {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<IpNetwork> {
+        read_inet(raw)
+    }
+
+    accepts!(Type::Inet, Type::Cidr);
+}
+
+impl ToSql for IpNetwork {
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, w: &mut W) -> Result<IsNull> {
+        try!(write_inet(ty, self, w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Inet, Type::Cidr);
+    to_sql_checked!();
+}
+
+/// Only host addresses, whose netmask covers the whole address, can be read
+/// as an `IpAddr`; use `IpNetwork` for other values.
+impl FromSql for IpAddr {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<IpAddr> {
+        let network = try!(read_inet(raw));
+        if network.prefix != max_prefix(&network.addr) {
+            let err = io::Error::new(io::ErrorKind::InvalidInput,
+                                     "addresses with a netmask cannot be read as an IpAddr");
+            return Err(Error::IoError(err));
+        }
+        Ok(network.addr)
+    }
+
+    accepts!(Type::Inet);
+}
+
+impl ToSql for IpAddr {
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, w: &mut W) -> Result<IsNull> {
+        let network = IpNetwork {
+            addr: *self,
+            prefix: max_prefix(self),
+        };
+        try!(write_inet(ty, &network, w));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Inet);
+    to_sql_checked!();
+}
+
+/// A MAC address, corresponding to the Postgres `MACADDR` type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddress([u8; 6]);
+
+impl MacAddress {
+    /// Creates a MAC address from its bytes.
+    pub fn new(bytes: [u8; 6]) -> MacAddress {
+        MacAddress(bytes)
+    }
+
+    /// Returns the bytes of the address.
+    pub fn bytes(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+/// Formats the address as Postgres does, like `08:00:2b:01:02:03`.
+impl fmt::Display for MacAddress {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let b = &self.0;
+        write!(fmt,
+               "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+               b[0], b[1], b[2], b[3], b[4], b[5])
+    }
+}
+
+impl FromSql for MacAddress {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<MacAddress> {
+        let mut bytes = [0; 6];
+        try!(util::read_all(raw, &mut bytes));
+        Ok(MacAddress(bytes))
+    }
+
+    accepts!(Type::Macaddr);
+}
+
+impl ToSql for MacAddress {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_all(&self.0));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Macaddr);
+    to_sql_checked!();
+}
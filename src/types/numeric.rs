@@ -0,0 +1,287 @@
+use std::ascii::AsciiExt;
+use std::error;
+use std::fmt;
+use std::io::prelude::*;
+use std::str::FromStr;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, FromSql, ToSql, IsNull};
+
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+
+const NBASE: i16 = 10000;
+const DEC_DIGITS: usize = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Sign {
+    Positive,
+    Negative,
+    NaN,
+}
+
+/// An arbitrary precision decimal number, corresponding to the Postgres
+/// `NUMERIC` type.
+///
+/// Values are stored as Postgres stores them, as digits in base 10000 along
+/// with a display scale, so they round-trip without any loss of precision.
+/// Equality compares representations, so `1.5` and `1.50` are not equal.
+///
+/// `Numeric` can be converted to and from strings with `Display` and
+/// `FromStr`.
+///
+/// # Examples
+///
+/// ```rust
+/// use postgres::types::Numeric;
+///
+/// let price: Numeric = "-1234.5600".parse().unwrap();
+/// assert_eq!(4, price.scale());
+/// assert_eq!("-1234.5600", price.to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Numeric {
+    sign: Sign,
+    weight: i16,
+    scale: u16,
+    digits: Vec<i16>,
+}
+
+impl Numeric {
+    /// Returns the `NaN` value.
+    pub fn nan() -> Numeric {
+        Numeric {
+            sign: Sign::NaN,
+            weight: 0,
+            scale: 0,
+            digits: vec![],
+        }
+    }
+
+    /// Determines if the value is `NaN`.
+    pub fn is_nan(&self) -> bool {
+        self.sign == Sign::NaN
+    }
+
+    /// Determines if the value is negative.
+    pub fn is_negative(&self) -> bool {
+        self.sign == Sign::Negative
+    }
+
+    /// Returns the display scale of the value, which is the number of digits
+    /// after the decimal point.
+    pub fn scale(&self) -> u16 {
+        self.scale
+    }
+
+    // Returns the base 10000 digit `weight` places to the left of the
+    // decimal point.
+    fn digit(&self, weight: i32) -> i16 {
+        let idx = self.weight as i32 - weight;
+        if idx < 0 || idx >= self.digits.len() as i32 {
+            0
+        } else {
+            self.digits[idx as usize]
+        }
+    }
+}
+
+impl fmt::Display for Numeric {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_nan() {
+            return fmt.write_str("NaN");
+        }
+
+        if self.is_negative() {
+            try!(fmt.write_str("-"));
+        }
+
+        if self.weight < 0 {
+            try!(fmt.write_str("0"));
+        } else {
+            try!(write!(fmt, "{}", self.digit(self.weight as i32)));
+            for weight in (0..self.weight as i32).rev() {
+                try!(write!(fmt, "{:04}", self.digit(weight)));
+            }
+        }
+
+        if self.scale > 0 {
+            let mut fraction = String::new();
+            let mut weight = -1;
+            while fraction.len() < self.scale as usize {
+                fraction.push_str(&format!("{:04}", self.digit(weight)));
+                weight -= 1;
+            }
+            try!(write!(fmt, ".{}", &fraction[..self.scale as usize]));
+        }
+
+        Ok(())
+    }
+}
+
+/// An error parsing a `Numeric` from a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseNumericError(());
+
+impl fmt::Display for ParseNumericError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(error::Error::description(self))
+    }
+}
+
+impl error::Error for ParseNumericError {
+    fn description(&self) -> &str {
+        "invalid decimal number"
+    }
+}
+
+// Converts a string of decimal digits, whose length is a multiple of 4, to
+// base 10000 digits.
+fn to_base_10000(digits: &str) -> Vec<i16> {
+    digits.as_bytes()
+          .chunks(DEC_DIGITS)
+          .map(|chunk| chunk.iter().fold(0, |acc, &b| acc * 10 + (b - b'0') as i16))
+          .collect()
+}
+
+impl FromStr for Numeric {
+    type Err = ParseNumericError;
+
+    fn from_str(s: &str) -> ::std::result::Result<Numeric, ParseNumericError> {
+        if s.eq_ignore_ascii_case("nan") {
+            return Ok(Numeric::nan());
+        }
+
+        let (negative, s) = if s.starts_with('-') {
+            (true, &s[1..])
+        } else if s.starts_with('+') {
+            (false, &s[1..])
+        } else {
+            (false, s)
+        };
+
+        let (integer, fraction) = match s.find('.') {
+            Some(idx) => (&s[..idx], &s[idx + 1..]),
+            None => (s, ""),
+        };
+        if (integer.is_empty() && fraction.is_empty())
+           || !integer.bytes().chain(fraction.bytes()).all(|b| b'0' <= b && b <= b'9')
+           || fraction.len() > u16::max_value() as usize {
+            return Err(ParseNumericError(()));
+        }
+
+        let integer = integer.trim_left_matches('0');
+        let int_digits = (integer.len() + DEC_DIGITS - 1) / DEC_DIGITS;
+        if int_digits > i16::max_value() as usize {
+            return Err(ParseNumericError(()));
+        }
+
+        // Pad both parts out to whole base 10000 digits
+        let mut padded = String::new();
+        for _ in integer.len()..int_digits * DEC_DIGITS {
+            padded.push('0');
+        }
+        padded.push_str(integer);
+        padded.push_str(fraction);
+        while padded.len() % DEC_DIGITS != 0 {
+            padded.push('0');
+        }
+
+        let mut digits = to_base_10000(&padded);
+        let mut weight = int_digits as i16 - 1;
+
+        // Like Postgres, strip leading and trailing zero digits
+        let leading = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading);
+        weight -= leading as i16;
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        if digits.is_empty() {
+            return Ok(Numeric {
+                sign: Sign::Positive,
+                weight: 0,
+                scale: fraction.len() as u16,
+                digits: digits,
+            });
+        }
+
+        Ok(Numeric {
+            sign: if negative { Sign::Negative } else { Sign::Positive },
+            weight: weight,
+            scale: fraction.len() as u16,
+            digits: digits,
+        })
+    }
+}
+
+impl From<i32> for Numeric {
+    fn from(value: i32) -> Numeric {
+        Numeric::from(value as i64)
+    }
+}
+
+impl From<i64> for Numeric {
+    fn from(value: i64) -> Numeric {
+        value.to_string().parse().unwrap()
+    }
+}
+
+impl FromSql for Numeric {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Numeric> {
+        let ndigits = try!(raw.read_u16::<BigEndian>());
+        let weight = try!(raw.read_i16::<BigEndian>());
+        let sign = match try!(raw.read_u16::<BigEndian>()) {
+            NUMERIC_POS => Sign::Positive,
+            NUMERIC_NEG => Sign::Negative,
+            NUMERIC_NAN => Sign::NaN,
+            _ => return Err(Error::BadResponse),
+        };
+        let scale = try!(raw.read_u16::<BigEndian>());
+
+        let mut digits = vec![];
+        for _ in 0..ndigits {
+            let digit = try!(raw.read_i16::<BigEndian>());
+            if digit < 0 || digit >= NBASE {
+                return Err(Error::BadResponse);
+            }
+            digits.push(digit);
+        }
+
+        Ok(Numeric {
+            sign: sign,
+            weight: weight,
+            scale: scale,
+            digits: digits,
+        })
+    }
+
+    accepts!(Type::Numeric);
+}
+
+impl ToSql for Numeric {
+    to_sql_checked!();
+
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        let sign = match self.sign {
+            Sign::Positive => NUMERIC_POS,
+            Sign::Negative => NUMERIC_NEG,
+            Sign::NaN => NUMERIC_NAN,
+        };
+
+        try!(w.write_u16::<BigEndian>(self.digits.len() as u16));
+        try!(w.write_i16::<BigEndian>(self.weight));
+        try!(w.write_u16::<BigEndian>(sign));
+        try!(w.write_u16::<BigEndian>(self.scale));
+        for &digit in &self.digits {
+            try!(w.write_i16::<BigEndian>(digit));
+        }
+
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Numeric);
+}
@@ -0,0 +1,338 @@
+use std::io;
+use std::io::prelude::*;
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+
+use {Result, Error};
+use types::{Type, Kind, FromSql, ToSql, IsNull};
+
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LB_INC: u8 = 0x02;
+const RANGE_UB_INC: u8 = 0x04;
+const RANGE_LB_INF: u8 = 0x08;
+const RANGE_UB_INF: u8 = 0x10;
+
+/// Whether a range bound includes its value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BoundType {
+    /// The bound includes its value.
+    Inclusive,
+    /// The bound excludes its value.
+    Exclusive,
+}
+
+/// One side of a `Range`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeBound<T> {
+    /// The value of the bound.
+    pub value: T,
+    /// Whether the bound includes its value.
+    pub type_: BoundType,
+}
+
+impl<T> RangeBound<T> {
+    /// Creates a bound which includes its value.
+    pub fn inclusive(value: T) -> RangeBound<T> {
+        RangeBound {
+            value: value,
+            type_: BoundType::Inclusive,
+        }
+    }
+
+    /// Creates a bound which excludes its value.
+    pub fn exclusive(value: T) -> RangeBound<T> {
+        RangeBound {
+            value: value,
+            type_: BoundType::Exclusive,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Inner<T> {
+    Empty,
+    Normal(Option<RangeBound<T>>, Option<RangeBound<T>>),
+}
+
+/// A Postgres range, such as `INT4RANGE`, `TSTZRANGE` or a custom range type.
+///
+/// A missing bound is unbounded, corresponding to an infinite bound in
+/// Postgres.
+///
+/// Ranges are not canonicalized, so ranges of discrete types read from the
+/// server will be in Postgres's canonical form with an inclusive lower bound
+/// and an exclusive upper bound, whatever form they were written in.
+///
+/// # Examples
+///
+/// ```rust
+/// use postgres::types::{Range, RangeBound};
+///
+/// let range = Range::new(Some(RangeBound::inclusive(1i32)), Some(RangeBound::exclusive(10)));
+/// assert!(range.contains(&1));
+/// assert!(!range.contains(&10));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range<T> {
+    inner: Inner<T>,
+}
+
+// Whether a range with these bounds contains any values
+fn nonempty<T: PartialOrd>(lower: Option<&RangeBound<T>>, upper: Option<&RangeBound<T>>) -> bool {
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => {
+            if lower.value == upper.value {
+                lower.type_ == BoundType::Inclusive && upper.type_ == BoundType::Inclusive
+            } else {
+                lower.value < upper.value
+            }
+        }
+        _ => true,
+    }
+}
+
+// The more restrictive of two lower bounds
+fn max_lower<'a, T: PartialOrd>(a: Option<&'a RangeBound<T>>, b: Option<&'a RangeBound<T>>)
+                                -> Option<&'a RangeBound<T>> {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            if a.value > b.value || (a.value == b.value && a.type_ == BoundType::Exclusive) {
+                Some(a)
+            } else {
+                Some(b)
+            }
+        }
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+// The more restrictive of two upper bounds
+fn min_upper<'a, T: PartialOrd>(a: Option<&'a RangeBound<T>>, b: Option<&'a RangeBound<T>>)
+                                -> Option<&'a RangeBound<T>> {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            if a.value < b.value || (a.value == b.value && a.type_ == BoundType::Exclusive) {
+                Some(a)
+            } else {
+                Some(b)
+            }
+        }
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+impl<T: PartialOrd> Range<T> {
+    /// Creates a new range with the specified bounds, where `None` indicates
+    /// an unbounded side.
+    ///
+    /// If the bounds don't enclose any values, the range is empty.
+    pub fn new(lower: Option<RangeBound<T>>, upper: Option<RangeBound<T>>) -> Range<T> {
+        if nonempty(lower.as_ref(), upper.as_ref()) {
+            Range { inner: Inner::Normal(lower, upper) }
+        } else {
+            Range::empty()
+        }
+    }
+
+    /// Creates a new empty range.
+    pub fn empty() -> Range<T> {
+        Range { inner: Inner::Empty }
+    }
+
+    /// Determines if the range is empty.
+    pub fn is_empty(&self) -> bool {
+        match self.inner {
+            Inner::Empty => true,
+            Inner::Normal(..) => false,
+        }
+    }
+
+    /// Returns the lower bound of the range, or `None` if it is unbounded
+    /// below or empty.
+    pub fn lower(&self) -> Option<&RangeBound<T>> {
+        match self.inner {
+            Inner::Normal(Some(ref lower), _) => Some(lower),
+            _ => None,
+        }
+    }
+
+    /// Returns the upper bound of the range, or `None` if it is unbounded
+    /// above or empty.
+    pub fn upper(&self) -> Option<&RangeBound<T>> {
+        match self.inner {
+            Inner::Normal(_, Some(ref upper)) => Some(upper),
+            _ => None,
+        }
+    }
+
+    /// Determines if a value lies within the range.
+    pub fn contains(&self, value: &T) -> bool {
+        let (lower, upper) = match self.inner {
+            Inner::Empty => return false,
+            Inner::Normal(ref lower, ref upper) => (lower, upper),
+        };
+
+        let above_lower = match *lower {
+            Some(RangeBound { value: ref bound, type_: BoundType::Inclusive }) => bound <= value,
+            Some(RangeBound { value: ref bound, type_: BoundType::Exclusive }) => bound < value,
+            None => true,
+        };
+        let below_upper = match *upper {
+            Some(RangeBound { value: ref bound, type_: BoundType::Inclusive }) => value <= bound,
+            Some(RangeBound { value: ref bound, type_: BoundType::Exclusive }) => value < bound,
+            None => true,
+        };
+        above_lower && below_upper
+    }
+
+    /// Determines if the range has any values in common with another.
+    pub fn overlaps(&self, other: &Range<T>) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+
+        nonempty(max_lower(self.lower(), other.lower()),
+                 min_upper(self.upper(), other.upper()))
+    }
+}
+
+impl<T: PartialOrd + Clone> Range<T> {
+    /// Returns the range of values contained in both this range and another.
+    pub fn intersection(&self, other: &Range<T>) -> Range<T> {
+        if self.is_empty() || other.is_empty() {
+            return Range::empty();
+        }
+
+        let lower = max_lower(self.lower(), other.lower());
+        let upper = min_upper(self.upper(), other.upper());
+        Range::new(lower.cloned(), upper.cloned())
+    }
+}
+
+fn read_bound<T, R>(ty: &Type, raw: &mut R, type_: BoundType) -> Result<RangeBound<T>>
+        where T: FromSql, R: Read {
+    let len = try!(raw.read_i32::<BigEndian>());
+    if len < 0 {
+        return Err(Error::BadResponse);
+    }
+
+    let mut limit = raw.take(len as u64);
+    let value = try!(<T as FromSql>::from_sql(ty, &mut limit));
+    if limit.limit() != 0 {
+        return Err(Error::BadResponse);
+    }
+
+    Ok(RangeBound {
+        value: value,
+        type_: type_,
+    })
+}
+
+fn bound_type(flags: u8, inclusive: u8) -> BoundType {
+    if flags & inclusive != 0 {
+        BoundType::Inclusive
+    } else {
+        BoundType::Exclusive
+    }
+}
+
+impl<T: FromSql> FromSql for Range<T> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Range<T>> {
+        let element_type = match ty.kind() {
+            &Kind::Range(ref element_type) => element_type,
+            _ => return Err(Error::WrongType(ty.clone())),
+        };
+
+        let flags = try!(raw.read_u8());
+        if flags & RANGE_EMPTY != 0 {
+            return Ok(Range { inner: Inner::Empty });
+        }
+
+        let lower = if flags & RANGE_LB_INF == 0 {
+            Some(try!(read_bound(element_type, &mut *raw, bound_type(flags, RANGE_LB_INC))))
+        } else {
+            None
+        };
+        let upper = if flags & RANGE_UB_INF == 0 {
+            Some(try!(read_bound(element_type, &mut *raw, bound_type(flags, RANGE_UB_INC))))
+        } else {
+            None
+        };
+
+        Ok(Range { inner: Inner::Normal(lower, upper) })
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match ty.kind() {
+            &Kind::Range(ref element_type) => <T as FromSql>::accepts(element_type),
+            _ => false,
+        }
+    }
+}
+
+fn write_bound<T, W>(ty: &Type, bound: &RangeBound<T>, buf: &mut Vec<u8>, mut w: &mut W)
+                     -> Result<()>
+        where T: ToSql, W: Write + ?Sized {
+    buf.clear();
+    match try!(bound.value.to_sql(ty, buf)) {
+        IsNull::No => {}
+        IsNull::Yes => {
+            let err = io::Error::new(io::ErrorKind::InvalidInput, "range bounds cannot be NULL");
+            return Err(Error::IoError(err));
+        }
+    }
+
+    try!(w.write_i32::<BigEndian>(buf.len() as i32));
+    try!(w.write_all(buf));
+    Ok(())
+}
+
+impl<T: ToSql> ToSql for Range<T> {
+    to_sql_checked!();
+
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, mut w: &mut W) -> Result<IsNull> {
+        let element_type = match ty.kind() {
+            &Kind::Range(ref element_type) => element_type,
+            _ => panic!("expected range type"),
+        };
+
+        let (lower, upper) = match self.inner {
+            Inner::Empty => {
+                try!(w.write_u8(RANGE_EMPTY));
+                return Ok(IsNull::No);
+            }
+            Inner::Normal(ref lower, ref upper) => (lower, upper),
+        };
+
+        let mut flags = 0;
+        match *lower {
+            Some(RangeBound { type_: BoundType::Inclusive, .. }) => flags |= RANGE_LB_INC,
+            Some(RangeBound { type_: BoundType::Exclusive, .. }) => {}
+            None => flags |= RANGE_LB_INF,
+        }
+        match *upper {
+            Some(RangeBound { type_: BoundType::Inclusive, .. }) => flags |= RANGE_UB_INC,
+            Some(RangeBound { type_: BoundType::Exclusive, .. }) => {}
+            None => flags |= RANGE_UB_INF,
+        }
+        try!(w.write_u8(flags));
+
+        let mut buf = vec![];
+        if let Some(ref lower) = *lower {
+            try!(write_bound(element_type, lower, &mut buf, &mut *w));
+        }
+        if let Some(ref upper) = *upper {
+            try!(write_bound(element_type, upper, &mut buf, &mut *w));
+        }
+
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match ty.kind() {
+            &Kind::Range(ref element_type) => <T as ToSql>::accepts(element_type),
+            _ => false,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serialize::json;
+use std::io;
 use std::io::prelude::*;
 use byteorder::{ReadBytesExt, WriteBytesExt};
 
@@ -13,7 +14,9 @@ impl FromSql for json::Json {
                 return Err(Error::BadResponse);
             }
         }
-        json::Json::from_reader(raw).map_err(|_| Error::BadResponse)
+        json::Json::from_reader(raw).map_err(|err| {
+            Error::IoError(io::Error::new(io::ErrorKind::InvalidInput, err))
+        })
     }
 
     accepts!(Type::Json, Type::Jsonb);
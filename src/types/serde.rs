@@ -1,33 +1,62 @@
 extern crate serde;
 
+use std::io;
 use std::io::prelude::*;
 use byteorder::{ReadBytesExt, WriteBytesExt};
+use self::serde::{Serialize, Deserialize};
 use self::serde::json::{self, Value};
 
 use {Result, Error};
 use types::{FromSql, ToSql, IsNull, Type};
 
-impl FromSql for Value {
-    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Value> {
+/// A wrapper mapping any `serde` serializable type to the Postgres `JSON` and
+/// `JSONB` types.
+///
+/// Values are serialized with `serde`'s JSON serializer when written, and
+/// deserialized directly into `T` when read.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # fn foo() -> postgres::Result<()> {
+/// # use postgres::{Connection, SslMode};
+/// use postgres::types::Json;
+///
+/// # let conn = Connection::connect("", &SslMode::None).unwrap();
+/// let stmt = try!(conn.prepare("SELECT '[1, 2, 3]'::JSONB"));
+/// let rows = try!(stmt.query(&[]));
+/// let Json(values): Json<Vec<i32>> = rows.iter().next().unwrap().get(0);
+/// assert_eq!(vec![1, 2, 3], values);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Json<T>(pub T);
+
+impl<T: Deserialize> FromSql for Json<T> {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Json<T>> {
         if let Type::Jsonb = *ty {
             // We only support version 1 of the jsonb binary format
             if try!(raw.read_u8()) != 1 {
                 return Err(Error::BadResponse);
             }
         }
-        json::de::from_reader(raw).map_err(|_| Error::BadResponse)
+
+        match json::de::from_reader(raw) {
+            Ok(value) => Ok(Json(value)),
+            Err(err) => Err(Error::IoError(io::Error::new(io::ErrorKind::InvalidInput, err))),
+        }
     }
 
     accepts!(Type::Json, Type::Jsonb);
 }
 
-impl ToSql for Value {
+impl<T: Serialize> ToSql for Json<T> {
     fn to_sql<W: Write+?Sized>(&self, ty: &Type, mut out: &mut W) -> Result<IsNull> {
         if let Type::Jsonb = *ty {
             try!(out.write_u8(1));
         }
 
-        try!(write!(out, "{:?}", self));
+        try!(json::to_writer(&mut out, &self.0));
 
         Ok(IsNull::No)
     }
@@ -35,3 +64,20 @@ impl ToSql for Value {
     accepts!(Type::Json, Type::Jsonb);
     to_sql_checked!();
 }
+
+impl FromSql for Value {
+    fn from_sql<R: Read>(ty: &Type, raw: &mut R) -> Result<Value> {
+        Json::<Value>::from_sql(ty, raw).map(|json| json.0)
+    }
+
+    accepts!(Type::Json, Type::Jsonb);
+}
+
+impl ToSql for Value {
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, out: &mut W) -> Result<IsNull> {
+        Json(self).to_sql(ty, out)
+    }
+
+    accepts!(Type::Json, Type::Jsonb);
+    to_sql_checked!();
+}
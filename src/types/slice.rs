@@ -1,8 +1,8 @@
 use std::io::prelude::*;
-use byteorder::{WriteBytesExt, BigEndian};
 
 use {Type, ToSql, Result, Error, Kind};
-use types::IsNull;
+use types::{IsNull, Dimension};
+use types::array::write_array;
 
 /// An adapter type mapping slices to Postgres arrays.
 ///
@@ -36,31 +36,17 @@ impl<'a, T: 'a + ToSql> ToSql for Slice<'a, T> {
         self.to_sql(ty, out)
     }
 
-    fn to_sql<W: Write+?Sized>(&self, ty: &Type, mut w: &mut W) -> Result<IsNull> {
+    fn to_sql<W: Write+?Sized>(&self, ty: &Type, w: &mut W) -> Result<IsNull> {
         let member_type = match ty.kind() {
             &Kind::Array(ref member) => member,
             _ => panic!("expected array type"),
         };
 
-        try!(w.write_i32::<BigEndian>(1)); // number of dimensions
-        try!(w.write_i32::<BigEndian>(1)); // has nulls
-        try!(w.write_u32::<BigEndian>(member_type.to_oid()));
-
-        try!(w.write_i32::<BigEndian>(self.0.len() as i32));
-        try!(w.write_i32::<BigEndian>(0)); // index offset
-
-        let mut inner_buf = vec![];
-        for e in self.0 {
-            match try!(e.to_sql(&member_type, &mut inner_buf)) {
-                IsNull::No => {
-                    try!(w.write_i32::<BigEndian>(inner_buf.len() as i32));
-                    try!(w.write_all(&inner_buf));
-                }
-                IsNull::Yes => try!(w.write_i32::<BigEndian>(-1)),
-            }
-            inner_buf.clear();
-        }
-
+        let dims = [Dimension {
+                        len: self.0.len(),
+                        lower_bound: 0,
+                    }];
+        try!(write_array(member_type, &dims, self.0, w));
         Ok(IsNull::No)
     }
 
@@ -2,20 +2,31 @@ extern crate time;
 
 use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
 use self::time::Timespec;
+use std::fmt;
 use std::io::prelude::*;
+use std::{i32, i64};
 
-use Result;
+use {Result, Error};
 use types::{Type, FromSql, ToSql, IsNull};
+use types::infinite::infinite_error;
 
 const USEC_PER_SEC: i64 = 1_000_000;
 const NSEC_PER_USEC: i64 = 1_000;
+const USEC_PER_DAY: i64 = 86_400 * USEC_PER_SEC;
 
 // Number of seconds from 1970-01-01 to 2000-01-01
 const TIME_SEC_CONVERSION: i64 = 946684800;
 
+// Number of days from 1970-01-01 to 2000-01-01
+const DATE_DAY_CONVERSION: i64 = 10957;
+
 impl FromSql for Timespec {
     fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Timespec> {
         let t = try!(raw.read_i64::<BigEndian>());
+        if t == i64::MAX || t == i64::MIN {
+            return Err(infinite_error());
+        }
+
         let mut sec = t / USEC_PER_SEC + TIME_SEC_CONVERSION;
         let mut usec = t % USEC_PER_SEC;
 
@@ -40,3 +51,277 @@ impl ToSql for Timespec {
     accepts!(Type::Timestamp, Type::TimestampTZ);
     to_sql_checked!();
 }
+
+// The number of days since 1970-01-01 of a date in the proleptic Gregorian
+// calendar, from http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// A calendar date, corresponding to the Postgres `DATE` type.
+///
+/// Years are numbered astronomically, so 1 BC is year 0. Use
+/// `Infinite<Date>` to handle `infinity` and `-infinity`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    // Days since 2000-01-01, as Postgres stores it
+    days: i32,
+}
+
+impl Date {
+    /// Creates a date from a year, month and day, returning `None` if it
+    /// doesn't exist.
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Date> {
+        if month < 1 || month > 12 || day < 1 {
+            return None;
+        }
+
+        let days = days_from_civil(year as i64, month as i64, day as i64) - DATE_DAY_CONVERSION;
+        // The extreme values are reserved for infinity and -infinity
+        if days <= i32::MIN as i64 || days >= i32::MAX as i64 {
+            return None;
+        }
+
+        let date = Date { days: days as i32 };
+        if date.day() == day {
+            Some(date)
+        } else {
+            None
+        }
+    }
+
+    fn ymd(&self) -> (i64, i64, i64) {
+        civil_from_days(self.days as i64 + DATE_DAY_CONVERSION)
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> i32 {
+        self.ymd().0 as i32
+    }
+
+    /// Returns the month, starting from 1.
+    pub fn month(&self) -> u32 {
+        self.ymd().1 as u32
+    }
+
+    /// Returns the day of the month, starting from 1.
+    pub fn day(&self) -> u32 {
+        self.ymd().2 as u32
+    }
+}
+
+/// Formats the date as Postgres does, like `2015-04-01` or `0044-03-15 BC`.
+impl fmt::Display for Date {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = self.ymd();
+        if year > 0 {
+            write!(fmt, "{:04}-{:02}-{:02}", year, month, day)
+        } else {
+            write!(fmt, "{:04}-{:02}-{:02} BC", 1 - year, month, day)
+        }
+    }
+}
+
+impl FromSql for Date {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Date> {
+        let days = try!(raw.read_i32::<BigEndian>());
+        if days == i32::MAX || days == i32::MIN {
+            return Err(infinite_error());
+        }
+        Ok(Date { days: days })
+    }
+
+    accepts!(Type::Date);
+}
+
+impl ToSql for Date {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_i32::<BigEndian>(self.days));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Date);
+    to_sql_checked!();
+}
+
+/// A time of day with microsecond precision, corresponding to the Postgres
+/// `TIME` type.
+///
+/// As in Postgres, `24:00:00` is a valid time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    // Microseconds since midnight
+    usec: i64,
+}
+
+impl Time {
+    /// Creates a time from an hour, minute, second and microsecond, returning
+    /// `None` if it is out of range.
+    pub fn from_hms_micro(hour: u32, minute: u32, second: u32, microsecond: u32)
+                          -> Option<Time> {
+        if minute >= 60 || second >= 60 || microsecond as i64 >= USEC_PER_SEC {
+            return None;
+        }
+
+        let usec = ((hour as i64 * 60 + minute as i64) * 60 + second as i64) * USEC_PER_SEC
+                   + microsecond as i64;
+        if usec > USEC_PER_DAY {
+            return None;
+        }
+
+        Some(Time { usec: usec })
+    }
+
+    /// Returns the hour.
+    pub fn hour(&self) -> u32 {
+        (self.usec / (3600 * USEC_PER_SEC)) as u32
+    }
+
+    /// Returns the minute.
+    pub fn minute(&self) -> u32 {
+        (self.usec / (60 * USEC_PER_SEC) % 60) as u32
+    }
+
+    /// Returns the second.
+    pub fn second(&self) -> u32 {
+        (self.usec / USEC_PER_SEC % 60) as u32
+    }
+
+    /// Returns the microsecond.
+    pub fn microsecond(&self) -> u32 {
+        (self.usec % USEC_PER_SEC) as u32
+    }
+}
+
+/// Formats the time as Postgres does, like `13:45:00` or `13:45:00.125`.
+impl fmt::Display for Time {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(fmt, "{:02}:{:02}:{:02}", self.hour(), self.minute(), self.second()));
+        if self.microsecond() != 0 {
+            let fraction = format!("{:06}", self.microsecond());
+            try!(write!(fmt, ".{}", fraction.trim_right_matches('0')));
+        }
+        Ok(())
+    }
+}
+
+fn read_time<R: Read>(raw: &mut R) -> Result<Time> {
+    let usec = try!(raw.read_i64::<BigEndian>());
+    if usec < 0 || usec > USEC_PER_DAY {
+        return Err(Error::BadResponse);
+    }
+    Ok(Time { usec: usec })
+}
+
+impl FromSql for Time {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Time> {
+        read_time(raw)
+    }
+
+    accepts!(Type::Time);
+}
+
+impl ToSql for Time {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_i64::<BigEndian>(self.usec));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Time);
+    to_sql_checked!();
+}
+
+/// A time of day with a UTC offset, corresponding to the Postgres `TIMETZ`
+/// type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimeTz {
+    /// The local time of day.
+    pub time: Time,
+    /// The offset from UTC in seconds, positive east of Greenwich.
+    pub offset: i32,
+}
+
+impl FromSql for TimeTz {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<TimeTz> {
+        let time = try!(read_time(raw));
+        // Postgres stores the offset west of Greenwich
+        let zone = try!(raw.read_i32::<BigEndian>());
+        Ok(TimeTz {
+            time: time,
+            offset: -zone,
+        })
+    }
+
+    accepts!(Type::Timetz);
+}
+
+impl ToSql for TimeTz {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_i64::<BigEndian>(self.time.usec));
+        try!(w.write_i32::<BigEndian>(-self.offset));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Timetz);
+    to_sql_checked!();
+}
+
+/// A time interval, corresponding to the Postgres `INTERVAL` type.
+///
+/// Like Postgres, the months, days and microseconds of an interval are kept
+/// separate, since the length of a month or day varies.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    /// The number of months.
+    pub months: i32,
+    /// The number of days.
+    pub days: i32,
+    /// The number of microseconds.
+    pub microseconds: i64,
+}
+
+impl FromSql for Interval {
+    fn from_sql<R: Read>(_: &Type, raw: &mut R) -> Result<Interval> {
+        let microseconds = try!(raw.read_i64::<BigEndian>());
+        let days = try!(raw.read_i32::<BigEndian>());
+        let months = try!(raw.read_i32::<BigEndian>());
+        Ok(Interval {
+            months: months,
+            days: days,
+            microseconds: microseconds,
+        })
+    }
+
+    accepts!(Type::Interval);
+}
+
+impl ToSql for Interval {
+    fn to_sql<W: Write+?Sized>(&self, _: &Type, mut w: &mut W) -> Result<IsNull> {
+        try!(w.write_i64::<BigEndian>(self.microseconds));
+        try!(w.write_i32::<BigEndian>(self.days));
+        try!(w.write_i32::<BigEndian>(self.months));
+        Ok(IsNull::No)
+    }
+
+    accepts!(Type::Interval);
+    to_sql_checked!();
+}
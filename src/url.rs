@@ -15,9 +15,13 @@ use serialize::hex::FromHex;
 pub struct Url {
     pub scheme: String,
     pub user: Option<UserInfo>,
+    pub hosts: Vec<Host>,
+    pub path: Path,
+}
+
+pub struct Host {
     pub host: String,
     pub port: Option<u16>,
-    pub path: Path,
 }
 
 pub struct Path {
@@ -36,8 +40,7 @@ pub type Query = Vec<(String, String)>;
 impl Url {
     pub fn new(scheme: String,
                user: Option<UserInfo>,
-               host: String,
-               port: Option<u16>,
+               hosts: Vec<Host>,
                path: String,
                query: Query,
                fragment: Option<String>)
@@ -45,8 +48,7 @@ impl Url {
         Url {
             scheme: scheme,
             user: user,
-            host: host,
-            port: port,
+            hosts: hosts,
             path: Path::new(path, query, fragment)
         }
     }
@@ -56,10 +58,10 @@ impl Url {
         let (scheme, rest) = try!(get_scheme(rawurl));
 
         // authority
-        let (userinfo, host, port, rest) = try!(get_authority(rest));
+        let (userinfo, hosts, rest) = try!(get_authorities(rest));
 
         // path
-        let has_authority = host.len() > 0;
+        let has_authority = hosts.iter().any(|h| !h.host.is_empty());
         let (path, rest) = try!(get_path(rest, has_authority));
 
         // query and fragment
@@ -67,8 +69,7 @@ impl Url {
 
         let url = Url::new(scheme.to_string(),
                             userinfo,
-                            host.to_string(),
-                            port,
+                            hosts,
                             path,
                             query,
                             fragment);
@@ -98,6 +99,13 @@ impl Path {
     }
 }
 
+impl Host {
+    #[inline]
+    pub fn new(host: String, port: Option<u16>) -> Host {
+        Host { host: host, port: port }
+    }
+}
+
 impl UserInfo {
     #[inline]
     pub fn new(user: String, pass: Option<String>) -> UserInfo {
@@ -198,6 +206,46 @@ pub fn get_scheme(rawurl: &str) -> DecodeResult<(&str, &str)> {
     Err("url: Scheme must be terminated with a colon.".to_string())
 }
 
+// returns userinfo, a list of hosts and ports, and unparsed part, or an error
+//
+// Multiple hosts are separated by commas, as in
+// `//user@host1:5432,host2:5433/db`.
+fn get_authorities(rawurl: &str) -> DecodeResult<(Option<UserInfo>, Vec<Host>, &str)> {
+    let end = if rawurl.starts_with("//") {
+        rawurl[2..].find(|c: char| c == '/' || c == '?' || c == '#')
+                   .map(|i| i + 2)
+                   .unwrap_or(rawurl.len())
+    } else {
+        0
+    };
+    let authority = if end > 2 { &rawurl[2..end] } else { "" };
+    let hosts_start = authority.rfind('@').map(|i| i + 1).unwrap_or(0);
+    let (rawuserinfo, rawhosts) = authority.split_at(hosts_start);
+
+    if !rawhosts.contains(',') {
+        let (userinfo, host, port, rest) = try!(get_authority(rawurl));
+        return Ok((userinfo, vec![Host::new(host.to_string(), port)], rest));
+    }
+
+    let mut userinfo = None;
+    let mut hosts = vec![];
+    for (i, rawhost) in rawhosts.split(',').enumerate() {
+        if rawhost.is_empty() {
+            return Err("Empty host in authority.".to_string());
+        }
+
+        // Parse each host as if it were the only one in the authority
+        let single = format!("//{}{}", rawuserinfo, rawhost);
+        let (user, host, port, _) = try!(get_authority(&single));
+        if i == 0 {
+            userinfo = user;
+        }
+        hosts.push(Host::new(host.to_string(), port));
+    }
+
+    Ok((userinfo, hosts, &rawurl[end..]))
+}
+
 // returns userinfo, host, port, and unparsed part, or an error
 fn get_authority(rawurl: &str) ->
     DecodeResult<(Option<UserInfo>, &str, Option<u16>, &str)> {
@@ -0,0 +1,97 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use postgres::{SslMode, GenericConnection};
+use postgres::pool::{Pool, Config, PoolError};
+
+fn config(min_size: u32, max_size: u32) -> Config {
+    Config {
+        min_size: min_size,
+        max_size: max_size,
+        checkout_timeout: Duration::from_millis(500),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn test_pool_min_size() {
+    let pool = or_panic!(Pool::new("postgres://postgres@localhost", SslMode::None, config(2, 4)));
+    let state = pool.state();
+    assert_eq!(2, state.connections);
+    assert_eq!(2, state.idle_connections);
+}
+
+#[test]
+fn test_pool_reuse() {
+    let pool = or_panic!(Pool::new("postgres://postgres@localhost", SslMode::None, config(0, 1)));
+    let pid = {
+        let conn = or_panic!(pool.get());
+        let stmt = or_panic!(conn.prepare("SELECT pg_backend_pid()"));
+        let pid: i32 = or_panic!(stmt.query(&[])).iter().next().unwrap().get(0);
+        pid
+    };
+    assert_eq!(1, pool.state().idle_connections);
+
+    let conn = or_panic!(pool.get());
+    let stmt = or_panic!(conn.prepare("SELECT pg_backend_pid()"));
+    assert_eq!(pid, or_panic!(stmt.query(&[])).iter().next().unwrap().get::<_, i32>(0));
+    assert_eq!(0, pool.state().idle_connections);
+}
+
+#[test]
+fn test_pool_checkout_timeout() {
+    let pool = or_panic!(Pool::new("postgres://postgres@localhost", SslMode::None, config(0, 1)));
+    let _conn = or_panic!(pool.get());
+    match pool.get() {
+        Err(PoolError::Timeout) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_pool_wakes_waiter() {
+    let pool = or_panic!(Pool::new("postgres://postgres@localhost", SslMode::None, config(0, 1)));
+    let conn = or_panic!(pool.get());
+
+    let (tx, rx) = mpsc::channel();
+    let pool2 = pool.clone();
+    let t = thread::spawn(move || {
+        tx.send(()).unwrap();
+        or_panic!(pool2.get());
+    });
+
+    rx.recv().unwrap();
+    drop(conn);
+    t.join().unwrap();
+}
+
+#[test]
+fn test_pool_connect_error() {
+    let pool = or_panic!(Pool::new("postgres://baduser@localhost", SslMode::None, config(0, 1)));
+    match pool.get() {
+        Err(PoolError::ConnectError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+    assert_eq!(0, pool.state().connections);
+}
+
+#[test]
+fn test_pool_generic_connection() {
+    fn count<C: GenericConnection>(conn: &C) -> i64 {
+        let stmt = or_panic!(conn.prepare("SELECT count(*) FROM foo"));
+        let count: i64 = or_panic!(stmt.query(&[])).iter().next().unwrap().get(0);
+        count
+    }
+
+    let pool = or_panic!(Pool::new("postgres://postgres@localhost", SslMode::None, config(0, 1)));
+    let conn = or_panic!(pool.get());
+    or_panic!(conn.batch_execute("CREATE TEMPORARY TABLE foo (id INT);
+                                  INSERT INTO foo VALUES (1);"));
+    assert_eq!(1, count(&conn));
+
+    let trans = or_panic!(conn.transaction());
+    or_panic!(trans.execute("INSERT INTO foo VALUES (2)", &[]));
+    assert_eq!(2, count(&trans));
+    or_panic!(trans.finish());
+}
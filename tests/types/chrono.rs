@@ -0,0 +1,101 @@
+extern crate chrono;
+
+use self::chrono::{Duration, NaiveDate, NaiveTime, NaiveDateTime, DateTime, UTC, FixedOffset,
+                   Offset};
+use postgres::{Connection, SslMode, Error};
+use postgres::types::Infinite;
+use types::test_type;
+
+#[test]
+fn test_naive_date_time_params() {
+    fn make_check<'a>(time: &'a str) -> (Option<NaiveDateTime>, &'a str) {
+        (Some(NaiveDateTime::parse_from_str(time, "'%Y-%m-%d %H:%M:%S%.f'").unwrap()), time)
+    }
+    test_type("TIMESTAMP",
+              &[make_check("'1970-01-01 00:00:00.01'"),
+                make_check("'1965-09-25 11:19:33.100314'"),
+                make_check("'2010-02-09 23:11:45.1202'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_date_time_params() {
+    fn make_check<'a>(time: &'a str) -> (Option<DateTime<UTC>>, &'a str) {
+        let naive = NaiveDateTime::parse_from_str(time, "'%Y-%m-%d %H:%M:%S%.f'").unwrap();
+        (Some(DateTime::from_utc(naive, UTC)), time)
+    }
+    test_type("TIMESTAMP WITH TIME ZONE",
+              &[make_check("'1970-01-01 00:00:00.01'"),
+                make_check("'1965-09-25 11:19:33.100314'"),
+                make_check("'2010-02-09 23:11:45.1202'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_fixed_offset_params() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let time = DateTime::parse_from_rfc3339("2015-04-01T09:00:00+02:00").unwrap();
+    let stmt = or_panic!(conn.prepare("SELECT $1::TIMESTAMPTZ = '2015-04-01 07:00:00+00', \
+                                              $1::TIMESTAMPTZ"));
+    let rows = or_panic!(stmt.query(&[&time]));
+    let row = rows.iter().next().unwrap();
+    assert!(row.get::<_, bool>(0));
+    let result: DateTime<FixedOffset> = row.get(1);
+    assert_eq!(time, result);
+    assert_eq!(Duration::zero(), result.offset().local_minus_utc());
+}
+
+#[test]
+fn test_naive_date_params() {
+    test_type("DATE",
+              &[(Some(NaiveDate::from_ymd(2015, 4, 1)), "'2015-04-01'"),
+                (Some(NaiveDate::from_ymd(2000, 1, 1)), "'2000-01-01'"),
+                (Some(NaiveDate::from_ymd(1900, 2, 28)), "'1900-02-28'"),
+                (Some(NaiveDate::from_ymd(-43, 3, 15)), "'0044-03-15 BC'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_naive_time_params() {
+    test_type("TIME",
+              &[(Some(NaiveTime::from_hms(0, 0, 0)), "'00:00:00'"),
+                (Some(NaiveTime::from_hms_micro(13, 45, 6, 789012)), "'13:45:06.789012'"),
+                (Some(NaiveTime::from_hms_micro(23, 59, 59, 999999)), "'23:59:59.999999'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_chrono_infinite_params() {
+    test_type("TIMESTAMP",
+              &[(Some(Infinite::Value(NaiveDate::from_ymd(2015, 4, 1).and_hms(9, 0, 0))),
+                 "'2015-04-01 09:00:00'"),
+                (Some(Infinite::PosInfinity), "'infinity'"),
+                (Some(Infinite::NegInfinity), "'-infinity'"),
+                (None, "NULL")]);
+    test_type("DATE",
+              &[(Some(Infinite::Value(NaiveDate::from_ymd(2015, 4, 1))), "'2015-04-01'"),
+                (Some(Infinite::PosInfinity), "'infinity'"),
+                (Some(Infinite::NegInfinity), "'-infinity'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_chrono_infinite_rejected() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT 'infinity'::TIMESTAMP, '-infinity'::DATE, \
+                                              '24:00:00'::TIME"));
+    let rows = or_panic!(stmt.query(&[]));
+    let row = rows.iter().next().unwrap();
+    match row.get_opt::<_, NaiveDateTime>(0) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+    match row.get_opt::<_, NaiveDate>(1) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+    match row.get_opt::<_, NaiveTime>(2) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
@@ -2,14 +2,19 @@ use std::collections::HashMap;
 use std::f32;
 use std::f64;
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 use postgres::{Connection, SslMode, Slice, Error};
-use postgres::types::{ToSql, FromSql};
+use postgres::types::{Type, ToSql, FromSql, Kind, Range, RangeBound, Array, Dimension,
+                      Numeric, IpNetwork, MacAddress, Point, LineSegment, BoundingBox,
+                      Path, Polygon, Line, Circle, BitVec};
 
 #[cfg(feature = "uuid")]
 mod uuid;
 #[cfg(feature = "time")]
 mod time;
+#[cfg(feature = "chrono")]
+mod chrono;
 #[cfg(feature = "rustc-serialize")]
 mod rustc_serialize;
 #[cfg(feature = "serde")]
@@ -220,3 +225,477 @@ fn test_slice_range() {
         Err(e) => panic!("Unexpected error {:?}", e),
     }
 }
+
+#[test]
+fn test_int4range_params() {
+    test_type("INT4RANGE",
+              &[(Some(Range::new(Some(RangeBound::inclusive(1i32)),
+                                 Some(RangeBound::exclusive(10)))), "'[1,10)'"),
+                (Some(Range::new(None, Some(RangeBound::exclusive(-5i32)))), "'(,-5)'"),
+                (Some(Range::new(Some(RangeBound::inclusive(100i32)), None)), "'[100,)'"),
+                (Some(Range::new(None, None)), "'(,)'"),
+                (Some(Range::empty()), "'empty'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_int8range_params() {
+    test_type("INT8RANGE",
+              &[(Some(Range::new(Some(RangeBound::inclusive(-9223372036854775708i64)),
+                                 Some(RangeBound::exclusive(9223372036854775708)))),
+                 "'[-9223372036854775708,9223372036854775708)'"),
+                (Some(Range::empty()), "'empty'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_range_canonicalized() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let range = Range::new(Some(RangeBound::exclusive(1i32)), Some(RangeBound::inclusive(5)));
+    let stmt = or_panic!(conn.prepare("SELECT $1::INT4RANGE"));
+    let result: Range<i32> = or_panic!(stmt.query(&[&range])).iter().next().unwrap().get(0);
+    assert_eq!(Range::new(Some(RangeBound::inclusive(2)), Some(RangeBound::exclusive(6))),
+               result);
+}
+
+#[test]
+fn test_custom_range() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    or_panic!(conn.batch_execute("CREATE TYPE pg_temp.floatrange AS RANGE (subtype = float8)"));
+
+    let range = Range::new(Some(RangeBound::exclusive(0.5f64)),
+                           Some(RangeBound::inclusive(1.5)));
+    let stmt = or_panic!(conn.prepare("SELECT $1::pg_temp.floatrange, \
+                                       '(0.5,1.5]'::pg_temp.floatrange = $1"));
+    let rows = or_panic!(stmt.query(&[&range]));
+    let row = rows.iter().next().unwrap();
+    assert_eq!(range, row.get::<_, Range<f64>>(0));
+    assert!(row.get::<_, bool>(1));
+}
+
+#[test]
+fn test_range_wrong_type() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+
+    let stmt = or_panic!(conn.prepare("SELECT $1::INT8RANGE"));
+    let range = Range::new(Some(RangeBound::inclusive(1i32)), None);
+    match stmt.query(&[&range]) {
+        Ok(_) => panic!("Unexpected success"),
+        Err(Error::WrongType(..)) => {}
+        Err(e) => panic!("Unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_range_operations() {
+    let a = Range::new(Some(RangeBound::inclusive(1i32)), Some(RangeBound::exclusive(10)));
+    let b = Range::new(Some(RangeBound::inclusive(10i32)), None);
+    let c = Range::new(Some(RangeBound::exclusive(5i32)), Some(RangeBound::inclusive(15)));
+
+    assert!(a.contains(&1));
+    assert!(a.contains(&9));
+    assert!(!a.contains(&10));
+    assert!(b.contains(&1000));
+    assert!(!Range::<i32>::empty().contains(&0));
+
+    assert!(!a.overlaps(&b));
+    assert!(a.overlaps(&c));
+    assert!(b.overlaps(&c));
+    assert!(!a.overlaps(&Range::empty()));
+
+    assert_eq!(Range::new(Some(RangeBound::exclusive(5)), Some(RangeBound::exclusive(10))),
+               a.intersection(&c));
+    assert_eq!(Range::new(Some(RangeBound::inclusive(10)), Some(RangeBound::inclusive(15))),
+               b.intersection(&c));
+    assert!(a.intersection(&b).is_empty());
+
+    assert!(Range::new(Some(RangeBound::exclusive(1i32)), Some(RangeBound::exclusive(1)))
+                .is_empty());
+    assert!(Range::new(Some(RangeBound::inclusive(2i32)), Some(RangeBound::inclusive(1)))
+                .is_empty());
+    assert!(!Range::new(Some(RangeBound::inclusive(1i32)), Some(RangeBound::inclusive(1)))
+                 .is_empty());
+}
+
+fn query_value<T: FromSql>(query: &str) -> T {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare(query));
+    let result = or_panic!(stmt.query(&[])).iter().next().unwrap().get(0);
+    result
+}
+
+#[test]
+fn test_vec_from_sql() {
+    assert_eq!(vec![1i32, 2, 3], query_value::<Vec<i32>>("SELECT '{1,2,3}'::INT4[]"));
+    assert_eq!(vec!["a".to_string(), "b c".to_string()],
+               query_value::<Vec<String>>("SELECT ARRAY['a', 'b c']"));
+    assert_eq!(vec![Some(1i64), None, Some(3)],
+               query_value::<Vec<Option<i64>>>("SELECT '{1,NULL,3}'::INT8[]"));
+    assert_eq!(Vec::<i32>::new(), query_value::<Vec<i32>>("SELECT '{}'::INT4[]"));
+    assert_eq!(None, query_value::<Option<Vec<i32>>>("SELECT NULL::INT4[]"));
+}
+
+#[test]
+fn test_vec_null_element() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT '{1,NULL}'::INT4[]"));
+    match or_panic!(stmt.query(&[])).iter().next().unwrap().get_opt::<_, Vec<i32>>(0) {
+        Err(Error::WasNull) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_vec_multi_dimensional() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT '{{1,2},{3,4}}'::INT4[]"));
+    match or_panic!(stmt.query(&[])).iter().next().unwrap().get_opt::<_, Vec<i32>>(0) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_array_from_sql() {
+    let array = query_value::<Array<Option<i32>>>(
+        "SELECT '[0:1][2:4]={{1,2,3},{4,NULL,6}}'::INT4[]");
+    assert_eq!(&[Dimension { len: 2, lower_bound: 0 }, Dimension { len: 3, lower_bound: 2 }],
+               array.dimensions());
+    assert_eq!(Some(&Some(1)), array.get(&[0, 2]));
+    assert_eq!(Some(&Some(3)), array.get(&[0, 4]));
+    assert_eq!(Some(&None), array.get(&[1, 3]));
+    assert_eq!(None, array.get(&[1, 5]));
+    assert_eq!(None, array.get(&[1]));
+    assert_eq!(vec![Some(1), Some(2), Some(3), Some(4), None, Some(6)], array.into_inner());
+
+    let array = query_value::<Array<String>>("SELECT '{}'::TEXT[]");
+    assert!(array.dimensions().is_empty());
+    assert_eq!(None, array.iter().next());
+}
+
+#[test]
+fn test_array_wrong_type() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT '{1,2}'::INT4[]"));
+    match or_panic!(stmt.query(&[])).iter().next().unwrap().get_opt::<_, Array<String>>(0) {
+        Err(Error::WrongType(..)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_array_params() {
+    test_type("INT4[]",
+              &[(Some(Array::from(vec![1i32, 2, 3])), "'{1,2,3}'"),
+                (Some(Array::from_vec(vec![1i32, 2], 0)), "'[0:1]={1,2}'"),
+                (Some(Array::from(vec![vec![1i32, 2], vec![3, 4]])), "'{{1,2},{3,4}}'"),
+                (Some(Array::from_vec(vec![], 1)), "'{}'"),
+                (None, "NULL")]);
+    test_type("TEXT[]",
+              &[(Some(Array::from(vec![Some("a".to_string()), None])), "'{a,NULL}'"),
+                (Some(Array::from(vec![None::<String>])), "'{NULL}'")]);
+}
+
+#[test]
+fn test_array_lower_bounds() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let array = Array::from_parts(vec![1i64, 2, 3, 4, 5, 6],
+                                  vec![Dimension { len: 3, lower_bound: -1 },
+                                       Dimension { len: 2, lower_bound: 5 }]);
+    let stmt = or_panic!(conn.prepare("SELECT $1::INT8[] = '[-1:1][5:6]={{1,2},{3,4},{5,6}}', \
+                                              ($1::INT8[])[0][6], \
+                                              array_dims($1::INT8[])"));
+    let rows = or_panic!(stmt.query(&[&array]));
+    let row = rows.iter().next().unwrap();
+    assert!(row.get::<_, bool>(0));
+    assert_eq!(4i64, row.get::<_, i64>(1));
+    assert_eq!("[-1:1][5:6]", row.get::<_, String>(2));
+}
+
+#[test]
+fn test_array_has_nulls() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT array_position($1::INT4[], NULL)"));
+    let with_null = Array::from(vec![Some(1i32), None]);
+    let without_null = Array::from(vec![Some(1i32), Some(2)]);
+    let position: Option<i32> = or_panic!(stmt.query(&[&with_null])).iter().next().unwrap().get(0);
+    assert_eq!(Some(2), position);
+    let position: Option<i32> =
+        or_panic!(stmt.query(&[&without_null])).iter().next().unwrap().get(0);
+    assert_eq!(None, position);
+}
+
+#[test]
+fn test_array_from_arrays() {
+    let rows: Vec<Array<i32>> = vec![Array::from(vec![vec![1, 2], vec![3, 4]]),
+                                     Array::from(vec![vec![5, 6], vec![7, 8]])];
+    let array = Array::from_arrays(rows);
+    assert_eq!(&[Dimension { len: 2, lower_bound: 1 },
+                 Dimension { len: 2, lower_bound: 1 },
+                 Dimension { len: 2, lower_bound: 1 }],
+               array.dimensions());
+    assert_eq!(Some(&7), array.get(&[2, 2, 1]));
+}
+
+#[test]
+#[should_panic]
+fn test_array_ragged() {
+    let _: Array<i32> = Array::from(vec![vec![1, 2], vec![3]]);
+}
+
+#[test]
+fn test_array_overflowing_dimensions() {
+    // Three dimensions of i32::MAX elements each, with no element data
+    let mut raw = vec![0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 23];
+    for _ in 0..3 {
+        raw.extend(&[0x7f, 0xff, 0xff, 0xff, 0, 0, 0, 1]);
+    }
+    match Array::<i32>::from_sql(&Type::Int4Array, &mut &raw[..]) {
+        Err(Error::BadResponse) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_array_from_parts_overflow() {
+    let dim = Dimension { len: usize::max_value(), lower_bound: 1 };
+    let _: Array<i32> = Array::from_parts(vec![], vec![dim, dim]);
+}
+
+#[test]
+fn test_numeric_params() {
+    fn make_check<'a>(s: &'a str) -> (Option<Numeric>, String) {
+        (Some(s.parse().unwrap()), format!("'{}'", s))
+    }
+    test_type("NUMERIC",
+              &[make_check("0"),
+                make_check("0.000"),
+                make_check("1"),
+                make_check("-1"),
+                make_check("10000"),
+                make_check("123456789.987654321"),
+                make_check("-0.00012"),
+                make_check("1234.5600"),
+                make_check("99999999999999999999999999999999999999.000000000000000000001"),
+                make_check("NaN"),
+                (None, "NULL".to_string())]);
+}
+
+#[test]
+fn test_numeric_array_params() {
+    test_type("NUMERIC[]",
+              &[(Some(Array::from(vec![Numeric::from(1), "2.50".parse().unwrap()])),
+                 "'{1,2.50}'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_numeric_display() {
+    for &s in &["0", "0.000", "-1", "10000", "0.0001", "-0.00012", "1234.5600", "NaN"] {
+        assert_eq!(s, s.parse::<Numeric>().unwrap().to_string());
+    }
+    assert_eq!("12", "+0012".parse::<Numeric>().unwrap().to_string());
+    assert_eq!("0.5", ".5".parse::<Numeric>().unwrap().to_string());
+    assert_eq!("0.0", "-0.0".parse::<Numeric>().unwrap().to_string());
+    assert_eq!("-9223372036854775808", Numeric::from(i64::min_value()).to_string());
+
+    for &s in &["", "-", ".", "1.2.3", "abc", "1e5", " 1"] {
+        assert!(s.parse::<Numeric>().is_err(), "{:?} parsed", s);
+    }
+}
+
+#[test]
+fn test_inet_params() {
+    test_type("INET",
+              &[(Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))), "'127.0.0.1'"),
+                (Some(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))),
+                 "'2001:db8::1'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_inet_netmask_rejected() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT '10.0.0.1/8'::INET"));
+    let rows = or_panic!(stmt.query(&[]));
+    match rows.iter().next().unwrap().get_opt::<_, IpAddr>(0) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_ip_network_params() {
+    fn make_check<'a>(addr: IpAddr, prefix: u8, repr: &'a str) -> (Option<IpNetwork>, &'a str) {
+        (Some(IpNetwork::new(addr, prefix).unwrap()), repr)
+    }
+    let v4 = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0));
+    let v6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0));
+    test_type("CIDR",
+              &[make_check(v4, 16, "'192.168.0.0/16'"),
+                make_check(v6, 32, "'2001:db8::/32'"),
+                (None, "NULL")]);
+    test_type("INET",
+              &[make_check(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 8, "'10.0.0.1/8'"),
+                make_check(v6, 128, "'2001:db8::'"),
+                (None, "NULL")]);
+    test_type("CIDR[]",
+              &[(Some(Array::from(vec![IpNetwork::new(v4, 16).unwrap()])),
+                 "'{192.168.0.0/16}'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_ip_network_new() {
+    let addr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0));
+    assert_eq!(None, IpNetwork::new(addr, 33));
+    assert_eq!("10.0.0.0/8", IpNetwork::new(addr, 8).unwrap().to_string());
+}
+
+#[test]
+fn test_macaddr_params() {
+    let mac = MacAddress::new([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]);
+    assert_eq!("08:00:2b:01:02:03", mac.to_string());
+    test_type("MACADDR", &[(Some(mac), "'08:00:2b:01:02:03'"), (None, "NULL")]);
+}
+
+#[test]
+fn test_point_params() {
+    test_type("POINT",
+              &[(Some(Point::new(1.5, -2.)), "'(1.5,-2)'"),
+                (None, "NULL")]);
+    test_type("POINT[]",
+              &[(Some(Array::from(vec![Point::new(0., 0.), Point::new(1., 1.)])),
+                 "ARRAY['(0,0)'::POINT, '(1,1)'::POINT]"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_lseg_params() {
+    let lseg = LineSegment {
+        start: Point::new(0., 0.),
+        end: Point::new(1., 2.),
+    };
+    test_type("LSEG", &[(Some(lseg), "'[(0,0),(1,2)]'"), (None, "NULL")]);
+}
+
+#[test]
+fn test_box_params() {
+    let box_ = BoundingBox {
+        high: Point::new(2., 3.),
+        low: Point::new(0., 1.),
+    };
+    test_type("BOX", &[(Some(box_), "'(2,3),(0,1)'"), (None, "NULL")]);
+    assert_eq!(box_, query_value::<BoundingBox>("SELECT '(0,3),(2,1)'::BOX"));
+}
+
+#[test]
+fn test_path_params() {
+    let points = vec![Point::new(0., 0.), Point::new(1., 1.), Point::new(2., 0.)];
+    test_type("PATH",
+              &[(Some(Path { points: points.clone(), closed: false }), "'[(0,0),(1,1),(2,0)]'"),
+                (Some(Path { points: points, closed: true }), "'((0,0),(1,1),(2,0))'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_polygon_params() {
+    let points = vec![Point::new(0., 0.), Point::new(1., 0.), Point::new(1., 1.)];
+    test_type("POLYGON",
+              &[(Some(Polygon { points: points }), "'((0,0),(1,0),(1,1))'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_line_params() {
+    test_type("LINE",
+              &[(Some(Line { a: 1., b: -1., c: 0.5 }), "'{1,-1,0.5}'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_circle_params() {
+    let circle = Circle {
+        center: Point::new(1., 2.),
+        radius: 1.5,
+    };
+    test_type("CIRCLE", &[(Some(circle), "'<(1,2),1.5>'"), (None, "NULL")]);
+}
+
+#[test]
+fn test_bit_params() {
+    let bits: BitVec = vec![true, false, true, true, false, false, true, false, true]
+                           .into_iter()
+                           .collect();
+    test_type("BIT(9)", &[(Some(bits.clone()), "B'101100101'"), (None, "NULL")]);
+    test_type("VARBIT",
+              &[(Some(bits), "B'101100101'"),
+                (Some(BitVec::from_bytes(&[0x0f])), "B'00001111'"),
+                (Some(BitVec::new()), "B''"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_bit_array_params() {
+    let array = vec![BitVec::from_elem(3, true), BitVec::from_elem(3, false)];
+    assert_eq!(array, query_value::<Vec<BitVec>>("SELECT '{111,000}'::BIT(3)[]"));
+
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT $1::BIT(3)[] = '{111,000}'"));
+    let result: bool = or_panic!(stmt.query(&[&Slice(&array)])).iter().next().unwrap().get(0);
+    assert!(result);
+}
+
+#[test]
+fn test_bit_vec_operations() {
+    let mut bits = BitVec::from_elem(10, true);
+    assert_eq!(&[0xff, 0xc0][..], bits.as_bytes());
+    bits.set(0, false);
+    bits.push(false);
+    assert_eq!(11, bits.len());
+    assert_eq!(Some(false), bits.get(0));
+    assert_eq!(None, bits.get(11));
+    assert_eq!("01111111110", bits.to_string());
+}
+
+#[derive(Debug, PartialEq)]
+enum Mood {
+    Sad,
+    Ok,
+    Happy,
+}
+
+pg_enum!(Mood {
+    Sad => "sad",
+    Ok => "ok",
+    Happy => "happy",
+});
+
+#[test]
+fn test_enum_params() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    or_panic!(conn.batch_execute("CREATE TYPE pg_temp.mood AS ENUM ('sad', 'ok', 'happy')"));
+
+    let stmt = or_panic!(conn.prepare("SELECT $1::pg_temp.mood"));
+    let labels = vec!["sad".to_owned(), "ok".to_owned(), "happy".to_owned()];
+    assert_eq!(&Kind::Enum(labels), stmt.param_types()[0].kind());
+
+    let result: Mood = or_panic!(stmt.query(&[&Mood::Happy])).iter().next().unwrap().get(0);
+    assert_eq!(Mood::Happy, result);
+    let result: String = or_panic!(stmt.query(&[&"sad"])).iter().next().unwrap().get(0);
+    assert_eq!("sad", result);
+}
+
+#[test]
+fn test_enum_wrong_labels() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    or_panic!(conn.batch_execute("CREATE TYPE pg_temp.mood AS ENUM ('sad', 'happy')"));
+
+    let stmt = or_panic!(conn.prepare("SELECT $1::pg_temp.mood"));
+    match stmt.query(&[&Mood::Happy]) {
+        Ok(_) => panic!("Unexpected success"),
+        Err(Error::WrongType(..)) => {}
+        Err(e) => panic!("Unexpected error {:?}", e),
+    }
+}
@@ -1,6 +1,8 @@
 extern crate serde;
 
 use self::serde::json::{self, Value};
+use postgres::{Connection, SslMode, Error};
+use postgres::types::Json;
 use types::test_type;
 
 #[test]
@@ -20,3 +22,23 @@ fn test_jsonb_params() {
                           "'{\"f\": \"asd\"}'"),
                          (None, "NULL")])
 }
+
+#[test]
+fn test_json_wrapper_params() {
+    for &ty in &["JSON", "JSONB"] {
+        test_type(ty, &[(Some(Json(vec![10i64, 11, 12])), "'[10, 11, 12]'"),
+                        (Some(Json(vec![])), "'[]'"),
+                        (None, "NULL")]);
+    }
+}
+
+#[test]
+fn test_json_wrapper_decode_error() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT '{\"f\": \"asd\"}'::JSONB"));
+    let rows = or_panic!(stmt.query(&[]));
+    match rows.iter().next().unwrap().get_opt::<_, Json<Vec<i64>>>(0) {
+        Err(Error::IoError(ref err)) if err.get_ref().is_some() => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
@@ -1,7 +1,9 @@
 extern crate time;
 
 use self::time::Timespec;
-use types::test_type;
+use postgres::{Connection, SslMode, Error};
+use postgres::types::{Range, RangeBound, Date, Time, TimeTz, Interval, Infinite};
+use types::{test_type, query_value};
 
 #[test]
 fn test_tm_params() {
@@ -20,3 +22,143 @@ fn test_tm_params() {
                (None, "NULL")]);
 }
 
+
+#[test]
+fn test_tsrange_params() {
+    fn make_check<'a>(lower: &str, upper: &str, repr: &'a str)
+                      -> (Option<Range<Timespec>>, &'a str) {
+        let lower = time::strptime(lower, "%Y-%m-%d %H:%M:%S").unwrap().to_timespec();
+        let upper = time::strptime(upper, "%Y-%m-%d %H:%M:%S").unwrap().to_timespec();
+        (Some(Range::new(Some(RangeBound::inclusive(lower)), Some(RangeBound::exclusive(upper)))),
+         repr)
+    }
+    let checks = &[make_check("2015-04-01 09:00:00",
+                              "2015-04-01 17:30:00",
+                              "'[2015-04-01 09:00:00,2015-04-01 17:30:00)'"),
+                   (Some(Range::empty()), "'empty'"),
+                   (None, "NULL")];
+    test_type("TSRANGE", checks);
+    test_type("TSTZRANGE", checks);
+}
+
+#[test]
+fn test_date_params() {
+    test_type("DATE",
+              &[(Some(Date::from_ymd(2015, 4, 1).unwrap()), "'2015-04-01'"),
+                (Some(Date::from_ymd(2000, 1, 1).unwrap()), "'2000-01-01'"),
+                (Some(Date::from_ymd(1900, 2, 28).unwrap()), "'1900-02-28'"),
+                (Some(Date::from_ymd(2400, 2, 29).unwrap()), "'2400-02-29'"),
+                (Some(Date::from_ymd(-43, 3, 15).unwrap()), "'0044-03-15 BC'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_date_fields() {
+    let date = Date::from_ymd(1999, 12, 31).unwrap();
+    assert_eq!((1999, 12, 31), (date.year(), date.month(), date.day()));
+    assert_eq!("1999-12-31", date.to_string());
+    assert_eq!("0001-01-01 BC", Date::from_ymd(0, 1, 1).unwrap().to_string());
+    assert!(date < Date::from_ymd(2000, 1, 1).unwrap());
+
+    assert_eq!(None, Date::from_ymd(2015, 2, 29));
+    assert_eq!(None, Date::from_ymd(2015, 4, 31));
+    assert_eq!(None, Date::from_ymd(2015, 13, 1));
+    assert_eq!(None, Date::from_ymd(2015, 1, 0));
+}
+
+#[test]
+fn test_time_params() {
+    test_type("TIME",
+              &[(Some(Time::from_hms_micro(0, 0, 0, 0).unwrap()), "'00:00:00'"),
+                (Some(Time::from_hms_micro(13, 45, 6, 789012).unwrap()), "'13:45:06.789012'"),
+                (Some(Time::from_hms_micro(24, 0, 0, 0).unwrap()), "'24:00:00'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_time_fields() {
+    let time = Time::from_hms_micro(13, 45, 6, 125000).unwrap();
+    assert_eq!((13, 45, 6, 125000),
+               (time.hour(), time.minute(), time.second(), time.microsecond()));
+    assert_eq!("13:45:06.125", time.to_string());
+    assert_eq!("09:00:00", Time::from_hms_micro(9, 0, 0, 0).unwrap().to_string());
+
+    assert_eq!(None, Time::from_hms_micro(24, 0, 0, 1));
+    assert_eq!(None, Time::from_hms_micro(12, 60, 0, 0));
+    assert_eq!(None, Time::from_hms_micro(12, 0, 0, 1000000));
+}
+
+#[test]
+fn test_timetz_params() {
+    test_type("TIMETZ",
+              &[(Some(TimeTz {
+                     time: Time::from_hms_micro(13, 45, 0, 0).unwrap(),
+                     offset: 2 * 3600,
+                 }),
+                 "'13:45:00+02'"),
+                (Some(TimeTz {
+                     time: Time::from_hms_micro(8, 0, 0, 0).unwrap(),
+                     offset: -(5 * 3600 + 30 * 60),
+                 }),
+                 "'08:00:00-05:30'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_interval_params() {
+    test_type("INTERVAL",
+              &[(Some(Interval {
+                     months: 14,
+                     days: 3,
+                     microseconds: ((4 * 60 + 5) * 60 + 6) * 1_000_000 + 789_000,
+                 }),
+                 "'1 year 2 mons 3 days 04:05:06.789'"),
+                (Some(Interval {
+                     months: -1,
+                     days: 0,
+                     microseconds: -1,
+                 }),
+                 "'-1 mons -00:00:00.000001'"),
+                (Some(Interval::default()), "'0'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_infinite_params() {
+    let timestamp = time::strptime("2015-04-01 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+                        .to_timespec();
+    test_type("TIMESTAMP",
+              &[(Some(Infinite::Value(timestamp)), "'2015-04-01 09:00:00'"),
+                (Some(Infinite::PosInfinity), "'infinity'"),
+                (Some(Infinite::NegInfinity), "'-infinity'"),
+                (None, "NULL")]);
+    test_type("DATE",
+              &[(Some(Infinite::Value(Date::from_ymd(2015, 4, 1).unwrap())), "'2015-04-01'"),
+                (Some(Infinite::PosInfinity), "'infinity'"),
+                (Some(Infinite::NegInfinity), "'-infinity'"),
+                (None, "NULL")]);
+}
+
+#[test]
+fn test_infinite_rejected() {
+    let conn = or_panic!(Connection::connect("postgres://postgres@localhost", &SslMode::None));
+    let stmt = or_panic!(conn.prepare("SELECT 'infinity'::TIMESTAMP, '-infinity'::DATE"));
+    let rows = or_panic!(stmt.query(&[]));
+    let row = rows.iter().next().unwrap();
+    match row.get_opt::<_, Timespec>(0) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+    match row.get_opt::<_, Date>(1) {
+        Err(Error::IoError(_)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_daterange_params() {
+    let range = Range::new(Some(RangeBound::inclusive(Date::from_ymd(2015, 4, 1).unwrap())),
+                           Some(RangeBound::exclusive(Date::from_ymd(2015, 4, 8).unwrap())));
+    assert_eq!(range, query_value::<Range<Date>>("SELECT '[2015-04-01,2015-04-07]'::DATERANGE"));
+    test_type("DATERANGE", &[(Some(range), "'[2015-04-01,2015-04-08)'"), (None, "NULL")]);
+}